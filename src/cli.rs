@@ -1,22 +1,49 @@
-use crate::data::Backend;
-use crate::effects::StackEffect;
+use crate::data::{self, Backend, HomeDir, Item, Recurrence};
+use crate::effects::{
+    Amendment, EffectResult, EventFilter, ItemRef, RestoreQuery, Retention, Search, StackEffect,
+    View,
+};
+use crate::error::SigiError;
 use crate::output::{NoiseLevel, OutputFormat};
-use clap::{Args, Parser, Subcommand, ValueEnum};
+use chrono::{DateTime, Local};
+use clap::error::ErrorKind;
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use regex::{Regex, RegexBuilder};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::{env, fs, process};
 use std::{error, fmt};
 
+#[cfg(feature = "tui")]
+pub mod choose;
+#[cfg(feature = "tui")]
+use choose::{Choose, ChooseAction};
+
+mod dates;
+
+mod edit;
+
+mod generate;
+
 mod interact;
 use interact::*;
 
+#[cfg(feature = "tui")]
+pub mod tui;
+
 /// The current version of the CLI. (As defined in Cargo.toml)
 pub const SIGI_VERSION: &str = std::env!("CARGO_PKG_VERSION");
 
 const DEFAULT_STACK_NAME: &str = "sigi";
 const DEFAULT_FORMAT: OutputFormat = OutputFormat::Human(NoiseLevel::Normal);
-const DEFAULT_BACKEND: Backend = Backend::HomeDir;
+const DEFAULT_REDIS_URL: &str = "redis://127.0.0.1/";
 const DEFAULT_SHORT_LIST_LIMIT: usize = 10;
+const INTERACTIVE_HISTORY_FILE: &str = "interactive_history.txt";
 
 // === Glossary ===
+const AMEND_TERMS: [&str; 1] = ["amend"];
+#[cfg(feature = "tui")]
+const CHOOSE_TERMS: [&str; 1] = ["choose"];
 const COMPLETE_TERMS: [&str; 4] = ["complete", "done", "finish", "fulfill"];
 const COUNT_TERMS: [&str; 3] = ["count", "size", "length"];
 const DELETE_TERMS: [&str; 5] = ["delete", "pop", "remove", "cancel", "drop"];
@@ -28,48 +55,183 @@ const DELETE_ALL_TERMS: [&str; 6] = [
     "cancel-all",
     "drop-all",
 ];
+const DUE_TERMS: [&str; 1] = ["due"];
+const DUP_TERMS: [&str; 1] = ["dup"];
+const EDIT_TERMS: [&str; 1] = ["edit"];
+const FIND_TERMS: [&str; 1] = ["find"];
+const HISTORY_TERMS: [&str; 2] = ["history", "log"];
 const HEAD_TERMS: [&str; 3] = ["head", "top", "first"];
 const IS_EMPTY_TERMS: [&str; 2] = ["is-empty", "empty"];
 const LIST_TERMS: [&str; 4] = ["list", "ls", "snoop", "all"];
 const LIST_STACKS_TERMS: [&str; 2] = ["list-stacks", "stacks"];
+const MINUS_ROT_TERMS: [&str; 1] = ["-rot"];
 const MOVE_TERMS: [&str; 1] = ["move"];
 const MOVE_ALL_TERMS: [&str; 1] = ["move-all"];
 const NEXT_TERMS: [&str; 4] = ["next", "later", "cycle", "bury"];
-const PEEK_TERMS: [&str; 2] = ["peek", "show"];
+const NIP_TERMS: [&str; 1] = ["nip"];
+const OVER_TERMS: [&str; 1] = ["over"];
+const OVERDUE_TERMS: [&str; 1] = ["overdue"];
+const PEEK_TERMS: [&str; 1] = ["peek"];
 const PICK_TERMS: [&str; 1] = ["pick"];
+const PRUNE_TERMS: [&str; 1] = ["prune"];
 const PUSH_TERMS: [&str; 6] = ["push", "create", "add", "do", "start", "new"];
+const REDO_TERMS: [&str; 1] = ["redo"];
+const RESTORE_TERMS: [&str; 3] = ["restore", "undelete", "reopen"];
+const RETENTION_TERMS: [&str; 1] = ["retention"];
+const ROLL_TERMS: [&str; 1] = ["roll"];
 const ROT_TERMS: [&str; 2] = ["rot", "rotate"];
+const SHOW_TERMS: [&str; 1] = ["show"];
+const SNOOZE_TERMS: [&str; 1] = ["snooze"];
 const SWAP_TERMS: [&str; 1] = ["swap"];
 const TAIL_TERMS: [&str; 3] = ["tail", "bottom", "last"];
+const TUCK_TERMS: [&str; 1] = ["tuck"];
+const UNDO_TERMS: [&str; 1] = ["undo"];
+const UNROLL_TERMS: [&str; 1] = ["unroll"];
 // === /glossary ===
 
 pub fn run() {
     let args = Cli::parse();
 
     let stack = args.stack.unwrap_or_else(|| DEFAULT_STACK_NAME.into());
+    let store = Store::resolve(args.backend, args.data_dir, args.redis_url);
 
-    match args.mode {
+    let (output, result) = match args.mode {
         None => {
             let output = args.fc.into_output_format().unwrap_or(DEFAULT_FORMAT);
-            let peek = StackEffect::Peek { stack };
-            peek.run(&DEFAULT_BACKEND, &output);
+            let view = View::default();
+            let peek = StackEffect::Peek { stack, view };
+            let result = store.and_then(|store| run_effect(peek, &store, &output));
+            (output, result)
+        }
+        #[cfg(feature = "tui")]
+        Some(Mode::Choose {
+            action,
+            dest,
+            all,
+            query,
+            select_1,
+            fc,
+        }) => {
+            let output = args.fc.into_fallback_for(fc);
+            let choose = Choose {
+                stack,
+                all_stacks: all,
+                query,
+                select_one: select_1,
+                action,
+                dest,
+            };
+            let result = store.and_then(|store| choose.run(store.open()?.as_ref()));
+            let result = result.map(|results| choose::log_chosen(&results, &output));
+            (output, result)
+        }
+        #[cfg(feature = "tui")]
+        Some(Mode::Command(Command::Pick {
+            fuzzy: true, fc, ..
+        })) => {
+            let output = args.fc.into_fallback_for(fc);
+            let choose = Choose {
+                stack,
+                all_stacks: false,
+                query: String::new(),
+                select_one: false,
+                action: ChooseAction::Pick,
+                dest: None,
+            };
+            let result = store.and_then(|store| choose.run(store.open()?.as_ref()));
+            let result = result.map(|results| choose::log_chosen(&results, &output));
+            (output, result)
         }
         Some(Mode::Command(command)) => {
             let (effect, effect_fc) = command.into_effect_and_fc(stack);
             let output = args.fc.into_fallback_for(effect_fc);
-            effect.run(&DEFAULT_BACKEND, &output);
+            let result = store.and_then(|store| run_effect(effect, &store, &output));
+            (output, result)
         }
         Some(Mode::Interactive { fc }) => {
             let output = args.fc.into_fallback_for(fc);
-            interact(stack, output);
+            let result = store.and_then(|store| {
+                let history = store.history_file()?;
+                interact(stack, store.open()?.as_ref(), output, Some(&history));
+                Ok(())
+            });
+            (output, result)
+        }
+        #[cfg(feature = "tui")]
+        Some(Mode::Tui) => {
+            let output = args.fc.into_output_format().unwrap_or(DEFAULT_FORMAT);
+            let result = store.and_then(|store| tui::run_tui(stack, store.open()?.as_ref()));
+            (output, result)
+        }
+        Some(Mode::ReadStdin) => {
+            let output = OutputFormat::TerseText;
+            let result = store.and_then(|store| {
+                interact(stack, store.open()?.as_ref(), output, None);
+                Ok(())
+            });
+            (output, result)
+        }
+        Some(Mode::Edit { item, fc }) => {
+            let output = args.fc.into_fallback_for(fc);
+            let result = store.and_then(|store| {
+                let item = item.unwrap_or(ItemRef::Current);
+                edit::edit_in_editor(stack, item, store.open()?.as_ref())
+            });
+            let result = result.map(|result| result.log(&output));
+            (output, result)
+        }
+        Some(Mode::Completions { shell }) => {
+            let output = args.fc.into_output_format().unwrap_or(DEFAULT_FORMAT);
+            let result = generate::print_completions(shell).map_err(SigiError::from);
+            (output, result)
+        }
+        Some(Mode::Manpage) => {
+            let output = args.fc.into_output_format().unwrap_or(DEFAULT_FORMAT);
+            let result = generate::print_manpage().map_err(SigiError::from);
+            (output, result)
+        }
+        Some(Mode::Init { fc }) => {
+            let output = args.fc.into_fallback_for(fc);
+            (output, init_project_dir(&output))
+        }
+        Some(Mode::Store { fc }) => {
+            let output = args.fc.into_fallback_for(fc);
+            let result = store.map(|store| store.log(&output));
+            (output, result)
         }
-        Some(Mode::ReadStdin) => interact(stack, OutputFormat::TerseText),
     };
+
+    if let Err(err) = result {
+        output.log_error(&err);
+        process::exit(err.exit_code());
+    }
+}
+
+fn run_effect(effect: StackEffect, store: &Store, output: &OutputFormat) -> Result<(), SigiError> {
+    let result = effect.run(store.open()?.as_ref())?;
+    result.log(output);
+
+    // Exit with a failure (nonzero status) when not empty.
+    // This helps people who do shell scripting do something like:
+    //     while ! sigi -t $stack is-empty ; do <ETC> ; done
+    if let EffectResult::IsEmpty { empty: false, .. } = result {
+        process::exit(1);
+    }
+    Ok(())
 }
 
 #[derive(Parser)]
-#[command(name = "sigi", version = SIGI_VERSION, after_help = INTERACT_INSTRUCTIONS, after_long_help = INTERACT_LONG_INSTRUCTIONS)]
+#[command(name = "sigi", version = SIGI_VERSION, author, after_help = INTERACT_INSTRUCTIONS, after_long_help = INTERACT_LONG_INSTRUCTIONS)]
 /// An organizing tool for terminal lovers who hate organizing
+///
+/// Use sigi as extra memory. Use it to organize your tasks, groceries, or the
+/// next board games you want to play... as stacks! Shell aliases are strongly
+/// encouraged to organize your various stacks.
+///
+/// Sigi is the Chamorro word for continue. I hope this will help you to plan
+/// more, forget less, get things done, and relax.
+///
+/// Examples, contributing, and support: https://github.com/sigi-cli/sigi
 struct Cli {
     #[command(flatten)]
     fc: FormatConfig,
@@ -78,6 +240,20 @@ struct Cli {
     /// Manage items in a specific stack
     stack: Option<String>,
 
+    #[arg(long, global = true, env = "SIGI_DATA_DIR")]
+    /// Keep stacks in this directory. By default, the closest ".sigi" directory
+    /// (see "init") is used, then the user's data directory
+    data_dir: Option<PathBuf>,
+
+    #[arg(long, env = "SIGI_BACKEND")]
+    /// Where stacks are stored. Options include [json, sqlite, redis]. The
+    /// first use of sqlite imports any existing json stacks
+    backend: Option<BackendKind>,
+
+    #[arg(long, env = "SIGI_REDIS_URL")]
+    /// The server to use with the redis backend [default: redis://127.0.0.1/]
+    redis_url: Option<String>,
+
     #[command(subcommand)]
     mode: Option<Mode>,
 }
@@ -91,18 +267,102 @@ enum Mode {
         fc: FormatConfig,
     },
 
+    /// Run in a full-screen terminal UI, with the stack, its history, and the
+    /// other stacks
+    #[cfg(feature = "tui")]
+    Tui,
+
     /// Read input lines from standard input. Same commands as interactive
     /// mode, but only prints for printing commands. Intended for use in unix
     /// pipes
     #[command(name = "-")]
     ReadStdin,
 
+    /// Choose items with a fuzzy finder, then pick (move to the top), complete,
+    /// delete, or move them. Type to filter, Tab to mark several items, and
+    /// Enter to choose
+    #[cfg(feature = "tui")]
+    #[command(visible_aliases = &CHOOSE_TERMS[1..])]
+    Choose {
+        #[arg(value_enum, default_value_t)]
+        /// What to do with the chosen items
+        action: ChooseAction,
+
+        #[arg(required_if_eq("action", "move"))]
+        /// The stack to move the chosen items to
+        dest: Option<String>,
+
+        #[arg(long)]
+        /// Choose from every stack
+        all: bool,
+
+        #[arg(long, default_value = "")]
+        /// Start with this text typed in
+        query: String,
+
+        #[arg(short = '1', long)]
+        /// If only one item matches the query, choose it without asking (and if
+        /// none do, choose nothing)
+        select_1: bool,
+
+        #[command(flatten)]
+        fc: FormatConfig,
+    },
+
+    /// Edit the current item's contents and notes in $VISUAL or $EDITOR. The
+    /// first line is the contents, and anything after a blank line is the notes
+    #[command(visible_aliases = &EDIT_TERMS[1..])]
+    Edit {
+        /// The number or ID of another item to edit
+        item: Option<ItemRef>,
+
+        #[command(flatten)]
+        fc: FormatConfig,
+    },
+
+    /// Print a completion script for a shell. For example, with bash:
+    /// sigi completions bash > ~/.local/share/bash-completion/completions/sigi
+    Completions {
+        /// The shell to complete for
+        shell: clap_complete::Shell,
+    },
+
+    /// Print the man page. For example: sigi manpage > ~/.local/share/man/man1/sigi.1
+    Manpage,
+
+    /// Create a ".sigi" directory here. Sigi uses the closest ".sigi" directory
+    /// in this directory or its parents to keep project-local stacks
+    Init {
+        #[command(flatten)]
+        fc: FormatConfig,
+    },
+
+    /// Print which backend and location are used to store stacks
+    #[command(visible_alias = "where")]
+    Store {
+        #[command(flatten)]
+        fc: FormatConfig,
+    },
+
     #[command(flatten)]
     Command(Command),
 }
 
 #[derive(Subcommand)]
 enum Command {
+    /// Change the current item's text, keeping its history. Give new text, or
+    /// "s/old/new/" (or "s/old/new/g") to replace part of it. To change another
+    /// item, put its number or ID first
+    #[command(arg_required_else_help = true, visible_aliases = &AMEND_TERMS[1..])]
+    Amend {
+        #[arg(required = true)]
+        /// The new text, optionally after the number or ID of the item
+        text: Vec<String>,
+
+        #[command(flatten)]
+        fc: FormatConfig,
+    },
+
     /// Move the current item to "<STACK>_history" and mark as completed
     #[command(visible_aliases = &COMPLETE_TERMS[1..])]
     Complete {
@@ -113,6 +373,10 @@ enum Command {
     /// Print the total number of items in the stack
     #[command(visible_aliases = &COUNT_TERMS[1..])]
     Count {
+        #[arg(long = "tag", value_parser = tag_arg)]
+        /// Only count items with this tag (e.g. "review" or "+review"). Can be repeated
+        tags: Vec<String>,
+
         #[command(flatten)]
         fc: FormatConfig,
     },
@@ -131,12 +395,90 @@ enum Command {
         fc: FormatConfig,
     },
 
+    /// Print items with due dates in every stack, soonest first
+    #[command(visible_aliases = &DUE_TERMS[1..])]
+    Due {
+        #[command(flatten)]
+        fc: FormatConfig,
+    },
+
+    /// Copy the current item to the top, as a new item
+    #[command(visible_aliases = &DUP_TERMS[1..])]
+    Dup {
+        #[command(flatten)]
+        fc: FormatConfig,
+    },
+
+    /// Print items containing some text, with their stack and number. Searches
+    /// the current stack unless told otherwise
+    #[command(arg_required_else_help = true, visible_aliases = &FIND_TERMS[1..])]
+    Find {
+        #[arg(required = true)]
+        /// The text to search for
+        text: Vec<String>,
+
+        #[arg(short, long)]
+        /// Match upper and lower case alike
+        ignore_case: bool,
+
+        #[arg(long)]
+        /// Treat the text as a regular expression
+        regex: bool,
+
+        #[arg(long)]
+        /// Search every stack
+        all: bool,
+
+        #[arg(long)]
+        /// Search "<STACK>_history" too, or every history stack with --all
+        history: bool,
+
+        #[command(flatten)]
+        fc: FormatConfig,
+    },
+
     /// Print the first N items (default is 10)
     #[command(visible_aliases = &HEAD_TERMS[1..])]
     Head {
         /// The number of items to display
         n: Option<usize>,
 
+        #[arg(long = "tag", value_parser = tag_arg)]
+        /// Only print items with this tag (e.g. "review" or "+review"). Can be repeated
+        tags: Vec<String>,
+
+        #[arg(long)]
+        /// Also print snoozed items
+        include_snoozed: bool,
+
+        #[command(flatten)]
+        fc: FormatConfig,
+    },
+
+    /// Print the events (created, completed, deleted, restored...) of items in
+    /// the stack and "<STACK>_history", most recent first
+    #[command(visible_aliases = &HISTORY_TERMS[1..])]
+    History {
+        #[arg(long, value_delimiter = ',')]
+        /// Only show these events, e.g. "completed,deleted"
+        event: Vec<String>,
+
+        #[arg(long, value_parser = dates::parse_start)]
+        /// Only show events on or after this date (e.g. 2026-11-01) or time
+        since: Option<DateTime<Local>>,
+
+        #[arg(long, value_parser = dates::parse_end)]
+        /// Only show events on or before this date (e.g. 2026-11-30) or time
+        until: Option<DateTime<Local>>,
+
+        #[arg(long)]
+        /// Only show items containing this text (ignoring case)
+        search: Option<String>,
+
+        #[arg(long, value_name = "ID")]
+        /// Only show every occurrence of the recurring item with this ID
+        series: Option<String>,
+
         #[command(flatten)]
         fc: FormatConfig,
     },
@@ -152,6 +494,14 @@ enum Command {
     /// Print all items
     #[command(visible_aliases = &LIST_TERMS[1..])]
     List {
+        #[arg(long = "tag", value_parser = tag_arg)]
+        /// Only print items with this tag (e.g. "review" or "+review"). Can be repeated
+        tags: Vec<String>,
+
+        #[arg(long)]
+        /// Also print snoozed items
+        include_snoozed: bool,
+
         #[command(flatten)]
         fc: FormatConfig,
     },
@@ -192,39 +542,173 @@ enum Command {
         fc: FormatConfig,
     },
 
+    /// Delete the second item, keeping the current one
+    #[command(visible_aliases = &NIP_TERMS[1..])]
+    Nip {
+        #[command(flatten)]
+        fc: FormatConfig,
+    },
+
+    /// Copy the second item to the top, as a new item
+    #[command(visible_aliases = &OVER_TERMS[1..])]
+    Over {
+        #[command(flatten)]
+        fc: FormatConfig,
+    },
+
+    /// Print items past their due dates in every stack, soonest first
+    #[command(visible_aliases = &OVERDUE_TERMS[1..])]
+    Overdue {
+        #[command(flatten)]
+        fc: FormatConfig,
+    },
+
     /// Print the first item. This is the default CLI behavior when no command is given
     #[command(visible_aliases = &PEEK_TERMS[1..])]
     Peek {
+        #[arg(long = "tag", value_parser = tag_arg)]
+        /// Print the first item with this tag (e.g. "review" or "+review"). Can be repeated
+        tags: Vec<String>,
+
         #[command(flatten)]
         fc: FormatConfig,
     },
 
-    /// Move items to the top of stack by their number
+    /// Move items to the top of stack by their number or ID
     #[command(visible_aliases = &PICK_TERMS[1..])]
     Pick {
-        ns: Vec<usize>,
+        /// The numbers or IDs of the items
+        items: Vec<ItemRef>,
+
+        #[cfg(feature = "tui")]
+        #[arg(long, conflicts_with = "items")]
+        /// Choose the items with a fuzzy finder instead (see "choose")
+        fuzzy: bool,
 
         #[command(flatten)]
         fc: FormatConfig,
     },
 
-    /// Create a new item
+    /// Remove old items from "<STACK>_history" for good, as set by "retention"
+    #[command(visible_aliases = &PRUNE_TERMS[1..])]
+    Prune {
+        #[arg(long)]
+        /// Only print what would be removed
+        dry_run: bool,
+
+        #[command(flatten)]
+        fc: FormatConfig,
+    },
+
+    /// Create a new item. Words like +review or #oncall in the content are tags
     #[command(visible_aliases = &PUSH_TERMS[1..])]
     Push {
         // The content to add as an item. Multiple arguments will be interpreted as a single string
         content: Vec<String>,
 
+        #[arg(long = "tag", value_parser = tag_arg)]
+        /// Add a tag (e.g. "review"), written as "+review" at the end of the content. Can be repeated
+        tags: Vec<String>,
+
+        #[arg(long, value_parser = dates::parse_due)]
+        /// When the item is due, e.g. "tomorrow", "fri" or "2026-11-01"
+        due: Option<DateTime<Local>>,
+
+        #[arg(long, value_name = "RULE")]
+        /// Push the item again when it's completed, due next time: "daily", "weekly", "every 3 days", or days like "mon,wed,fri"
+        repeat: Option<Recurrence>,
+
+        #[command(flatten)]
+        fc: FormatConfig,
+    },
+
+    /// Apply the last N undone commands again (default is 1)
+    #[command(visible_aliases = &REDO_TERMS[1..])]
+    Redo {
+        /// The number of commands to redo
+        n: Option<usize>,
+
+        #[command(flatten)]
+        fc: FormatConfig,
+    },
+
+    /// Move items from "<STACK>_history" back to the stack, by their number or
+    /// ID in the history or by searching. Without either, print the history
+    #[command(visible_aliases = &RESTORE_TERMS[1..])]
+    Restore {
+        /// The numbers or IDs of the items in the history
+        items: Vec<ItemRef>,
+
+        #[arg(long)]
+        /// Restore every item in the history containing this text (ignoring case)
+        search: Option<String>,
+
+        #[command(flatten)]
+        fc: FormatConfig,
+    },
+
+    /// Print or set how long "<STACK>_history" keeps items. Setting any option
+    /// replaces the whole policy
+    #[command(visible_aliases = &RETENTION_TERMS[1..])]
+    Retention {
+        #[arg(long, value_name = "DAYS")]
+        /// Prune items that went into the history more than this many days ago
+        max_age: Option<u32>,
+
+        #[arg(long, value_name = "N")]
+        /// Prune the oldest items beyond this many
+        max_count: Option<usize>,
+
+        #[arg(long)]
+        /// Prune automatically whenever items are completed or deleted
+        auto_prune: bool,
+
+        #[arg(long, conflicts_with_all = ["max_age", "max_count", "auto_prune"])]
+        /// Remove the policy, keeping all items
+        clear: bool,
+
         #[command(flatten)]
         fc: FormatConfig,
     },
 
-    /// Rotate the three most-current items
+    /// Move the item at position N to the top; "roll 2" undoes "rot"
+    #[command(arg_required_else_help = true, visible_aliases = &ROLL_TERMS[1..])]
+    Roll {
+        /// The number of the item to move
+        n: usize,
+
+        #[command(flatten)]
+        fc: FormatConfig,
+    },
+
+    /// Rotate the three most-current items; the current item becomes third
     #[command(visible_aliases = &ROT_TERMS[1..])]
     Rot {
         #[command(flatten)]
         fc: FormatConfig,
     },
 
+    /// Print the current item in full, with its notes
+    #[command(visible_aliases = &SHOW_TERMS[1..])]
+    Show {
+        /// The number or ID of another item to print
+        item: Option<ItemRef>,
+
+        #[command(flatten)]
+        fc: FormatConfig,
+    },
+
+    /// Hide the current item until later, after which it's current again
+    #[command(arg_required_else_help = true, visible_aliases = &SNOOZE_TERMS[1..])]
+    Snooze {
+        #[arg(value_parser = dates::parse_snooze)]
+        /// How long for (e.g. "30m", "2h", "3d") or until when (e.g. "tomorrow", "mon", "2026-11-01")
+        until: DateTime<Local>,
+
+        #[command(flatten)]
+        fc: FormatConfig,
+    },
+
     /// Swap the two most-current items
     #[command(visible_aliases = &SWAP_TERMS[1..])]
     Swap {
@@ -238,6 +722,49 @@ enum Command {
         /// The number of items to display
         n: Option<usize>,
 
+        #[arg(long = "tag", value_parser = tag_arg)]
+        /// Only print items with this tag (e.g. "review" or "+review"). Can be repeated
+        tags: Vec<String>,
+
+        #[arg(long)]
+        /// Also print snoozed items
+        include_snoozed: bool,
+
+        #[command(flatten)]
+        fc: FormatConfig,
+    },
+
+    /// Copy the current item below the second, as a new item
+    #[command(visible_aliases = &TUCK_TERMS[1..])]
+    Tuck {
+        #[command(flatten)]
+        fc: FormatConfig,
+    },
+
+    /// Undo the last N commands that changed any stack (default is 1)
+    #[command(visible_aliases = &UNDO_TERMS[1..])]
+    Undo {
+        /// The number of commands to undo
+        n: Option<usize>,
+
+        #[command(flatten)]
+        fc: FormatConfig,
+    },
+
+    /// Move the current item down to position N; the inverse of "roll"
+    #[command(arg_required_else_help = true, visible_aliases = &UNROLL_TERMS[1..])]
+    Unroll {
+        /// Where to move the current item
+        n: usize,
+
+        #[command(flatten)]
+        fc: FormatConfig,
+    },
+
+    /// Send the current item down to third, like Factor's -rot. The same as
+    /// "rot"
+    #[command(name = "-rot")]
+    MinusRot {
         #[command(flatten)]
         fc: FormatConfig,
     },
@@ -247,36 +774,199 @@ impl Command {
     fn into_effect_and_fc(self, stack: String) -> (StackEffect, FormatConfig) {
         use StackEffect::*;
         match self {
+            Command::Amend { mut text, fc } => {
+                // A leading number or ID picks the item, if there's text after it.
+                let item = match ItemRef::from_str(&text[0]) {
+                    Ok(item) if text.len() > 1 => {
+                        text.remove(0);
+                        item
+                    }
+                    _ => ItemRef::Current,
+                };
+                let amendment = Amendment::parse(&text.join(" "));
+                let amend = Amend {
+                    stack,
+                    item,
+                    amendment,
+                };
+                (amend, fc)
+            }
             Command::Complete { fc } => (Complete { stack }, fc),
-            Command::Count { fc } => (Count { stack }, fc),
+            Command::Count { tags, fc } => (Count { stack, tags }, fc),
             Command::Delete { fc } => (Delete { stack }, fc),
             Command::DeleteAll { fc } => (DeleteAll { stack }, fc),
-            Command::Head { n, fc } => {
+            Command::Due { fc } => (Due { overdue: false }, fc),
+            Command::Dup { fc } => (Dup { stack }, fc),
+            Command::Find {
+                text,
+                ignore_case,
+                regex,
+                all,
+                history,
+                fc,
+            } => {
+                let pattern = match search_pattern(&text.join(" "), ignore_case, regex) {
+                    Ok(pattern) => pattern,
+                    Err(err) => Cli::command().error(ErrorKind::ValueValidation, err).exit(),
+                };
+                let search = Search {
+                    pattern,
+                    all_stacks: all,
+                    history,
+                };
+                (Find { stack, search }, fc)
+            }
+            Command::Head {
+                n,
+                tags,
+                include_snoozed,
+                fc,
+            } => {
                 let n = n.unwrap_or(DEFAULT_SHORT_LIST_LIMIT);
-                (Head { n, stack }, fc)
+                let view = View {
+                    tags,
+                    snoozed: include_snoozed,
+                };
+                (Head { n, stack, view }, fc)
+            }
+            Command::History {
+                event,
+                since,
+                until,
+                search,
+                series,
+                fc,
+            } => {
+                let filter = EventFilter {
+                    events: event,
+                    since,
+                    until,
+                    search,
+                    series,
+                };
+                (History { stack, filter }, fc)
             }
             Command::IsEmpty { fc } => (IsEmpty { stack }, fc),
-            Command::List { fc } => (ListAll { stack }, fc),
+            Command::List {
+                tags,
+                include_snoozed,
+                fc,
+            } => {
+                let view = View {
+                    tags,
+                    snoozed: include_snoozed,
+                };
+                (ListAll { stack, view }, fc)
+            }
             Command::ListStacks { fc } => (ListStacks, fc),
             Command::Move { dest, fc } => (Move { stack, dest }, fc),
             Command::MoveAll { dest, fc } => (MoveAll { stack, dest }, fc),
             Command::Next { fc } => (Next { stack }, fc),
-            Command::Peek { fc } => (Peek { stack }, fc),
-            Command::Pick { ns, fc } => (Pick { stack, indices: ns }, fc),
-            Command::Push { content, fc } => {
-                let content = content.join(" ");
-                (Push { stack, content }, fc)
+            Command::Nip { fc } => (Nip { stack }, fc),
+            Command::Over { fc } => (Over { stack }, fc),
+            Command::Overdue { fc } => (Due { overdue: true }, fc),
+            Command::Peek { tags, fc } => {
+                let view = View {
+                    tags,
+                    snoozed: false,
+                };
+                (Peek { stack, view }, fc)
             }
+            Command::Pick { items, fc, .. } => (Pick { stack, items }, fc),
+            Command::Prune { dry_run, fc } => (Prune { stack, dry_run }, fc),
+            Command::Push {
+                content,
+                tags,
+                due,
+                repeat,
+                fc,
+            } => {
+                let mut content = content.join(" ");
+                // Tags from --tag are written out like any other tag.
+                let written = data::tags_in(&content);
+                for tag in tags.iter().filter(|tag| !written.contains(tag)) {
+                    content.push_str(" +");
+                    content.push_str(tag);
+                }
+                let mut item = Item::new(&content);
+                item.due = due;
+                item.recurrence = repeat;
+                (Push { stack, item }, fc)
+            }
+            Command::Redo { n, fc } => (Redo { n: n.unwrap_or(1) }, fc),
+            Command::Restore { items, search, fc } => {
+                let query = RestoreQuery { items, search };
+                (Restore { stack, query }, fc)
+            }
+            Command::Retention {
+                max_age,
+                max_count,
+                auto_prune,
+                clear,
+                fc,
+            } => {
+                let policy = Retention {
+                    max_age_days: max_age,
+                    max_count,
+                    auto_prune,
+                };
+                if clear || policy != Retention::default() {
+                    (SetRetention { stack, policy }, fc)
+                } else {
+                    (ShowRetention { stack }, fc)
+                }
+            }
+            Command::Roll { n, fc } => (Roll { stack, n }, fc),
             Command::Rot { fc } => (Rot { stack }, fc),
+            Command::Show { item, fc } => {
+                let item = item.unwrap_or(ItemRef::Current);
+                (Show { stack, item }, fc)
+            }
+            Command::Snooze { until, fc } => (Snooze { stack, at: until }, fc),
             Command::Swap { fc } => (Swap { stack }, fc),
-            Command::Tail { n, fc } => {
+            Command::Tail {
+                n,
+                tags,
+                include_snoozed,
+                fc,
+            } => {
                 let n = n.unwrap_or(DEFAULT_SHORT_LIST_LIMIT);
-                (Tail { n, stack }, fc)
+                let view = View {
+                    tags,
+                    snoozed: include_snoozed,
+                };
+                (Tail { n, stack, view }, fc)
             }
+            Command::Tuck { fc } => (Tuck { stack }, fc),
+            Command::Undo { n, fc } => (Undo { n: n.unwrap_or(1) }, fc),
+            Command::Unroll { n, fc } => (Unroll { stack, n }, fc),
+            Command::MinusRot { fc } => (MinusRot { stack }, fc),
         }
     }
 }
 
+/// The pattern to find items with. Plain text matches anywhere in an item.
+fn search_pattern(text: &str, ignore_case: bool, regex: bool) -> Result<Regex, regex::Error> {
+    let text = if regex {
+        text.to_string()
+    } else {
+        regex::escape(text)
+    };
+    RegexBuilder::new(&text)
+        .case_insensitive(ignore_case)
+        .build()
+}
+
+/// Parse a tag given as an argument, like "review" or "+review".
+fn tag_arg(s: &str) -> Result<String, String> {
+    data::parse_tag(s).ok_or_else(|| {
+        format!(
+            "expected a tag like +review, starting with a letter, but got {:?}",
+            s
+        )
+    })
+}
+
 #[derive(Args)]
 struct FormatConfig {
     #[arg(short, long)]
@@ -333,6 +1023,115 @@ impl FormatConfig {
     }
 }
 
+fn init_project_dir(output: &OutputFormat) -> Result<(), SigiError> {
+    let path = Path::new(data::PROJECT_DIR_NAME);
+    let action = if path.is_dir() {
+        "Already initialized"
+    } else {
+        fs::create_dir_all(path)?;
+        "Initialized"
+    };
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    output.log(
+        vec!["action", "path"],
+        vec![vec![action, &path.to_string_lossy()]],
+    );
+    Ok(())
+}
+
+#[derive(ValueEnum, Clone, Copy, Default)]
+enum BackendKind {
+    #[default]
+    Json,
+    Sqlite,
+    Redis,
+}
+
+/// For backends left out when sigi was built.
+#[cfg(not(all(feature = "sqlite", feature = "redis")))]
+fn not_built_with(feature: &str) -> SigiError {
+    SigiError::Backend(format!(
+        "This sigi was built without the {:?} feature",
+        feature
+    ))
+}
+
+/// The backend in effect, where it keeps its data, and why it was chosen.
+struct Store {
+    kind: BackendKind,
+    location: String,
+    source: &'static str,
+}
+
+impl Store {
+    fn resolve(
+        kind: Option<BackendKind>,
+        data_dir: Option<PathBuf>,
+        redis_url: Option<String>,
+    ) -> Result<Self, SigiError> {
+        let kind = kind.unwrap_or_default();
+
+        let (location, source) = match kind {
+            BackendKind::Redis => match redis_url {
+                Some(url) => (url, "option"),
+                None => (DEFAULT_REDIS_URL.to_string(), "default"),
+            },
+            BackendKind::Json | BackendKind::Sqlite => {
+                let (dir, source) = if let Some(dir) = data_dir {
+                    (dir, "option")
+                } else if let Some(dir) = env::current_dir()
+                    .ok()
+                    .and_then(|cwd| data::find_project_dir(&cwd))
+                {
+                    (dir, "project")
+                } else {
+                    (data::default_data_dir()?, "default")
+                };
+                (dir.to_string_lossy().to_string(), source)
+            }
+        };
+
+        Ok(Store {
+            kind,
+            location,
+            source,
+        })
+    }
+
+    fn open(&self) -> Result<Box<dyn Backend>, SigiError> {
+        let location = &self.location;
+        Ok(match self.kind {
+            BackendKind::Json => Box::new(HomeDir::at(Path::new(location))),
+            #[cfg(feature = "sqlite")]
+            BackendKind::Sqlite => Box::new(data::Sqlite::open(Path::new(location))?),
+            #[cfg(not(feature = "sqlite"))]
+            BackendKind::Sqlite => return Err(not_built_with("sqlite")),
+            #[cfg(feature = "redis")]
+            BackendKind::Redis => Box::new(data::Redis::open(location)?),
+            #[cfg(not(feature = "redis"))]
+            BackendKind::Redis => return Err(not_built_with("redis")),
+        })
+    }
+
+    /// Where interactive mode keeps the lines typed into it: beside the
+    /// stacks, or in the default data directory for redis.
+    fn history_file(&self) -> Result<PathBuf, SigiError> {
+        let dir = match self.kind {
+            BackendKind::Json | BackendKind::Sqlite => PathBuf::from(&self.location),
+            BackendKind::Redis => data::default_data_dir()?,
+        };
+        Ok(dir.join(INTERACTIVE_HISTORY_FILE))
+    }
+
+    fn log(&self, output: &OutputFormat) {
+        let kind = self.kind.to_possible_value().unwrap();
+        output.log_always(
+            vec!["backend", "location", "source"],
+            vec![vec![kind.get_name(), &self.location, self.source]],
+        );
+    }
+}
+
 #[derive(ValueEnum, Clone)]
 enum ProgrammaticFormat {
     Csv,
@@ -0,0 +1,344 @@
+use super::tui::read_key;
+use crate::data::{Backend, Item};
+use crate::effects::{EffectResult, ItemRef, StackEffect, View, HISTORY_SUFFIX};
+use crate::error::SigiError;
+use crate::output::OutputFormat;
+use clap::ValueEnum;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{List, ListState, Paragraph};
+use ratatui::Terminal;
+use std::io::{self, IsTerminal};
+
+const PROMPT: &str = "> ";
+
+/// What to do with chosen items.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ChooseAction {
+    /// Move them to the top of their stack
+    #[default]
+    Pick,
+    /// Complete them
+    Complete,
+    /// Delete them
+    Delete,
+    /// Move them to another stack
+    Move,
+}
+
+/// Which items to choose from, and how.
+pub struct Choose {
+    pub stack: String,
+    /// Choose from every stack, not just the current one.
+    pub all_stacks: bool,
+    /// Text to start filtering with.
+    pub query: String,
+    /// If only one item matches the query, choose it without asking. If none
+    /// do, choose nothing.
+    pub select_one: bool,
+    pub action: ChooseAction,
+    /// Where to move chosen items, for [`ChooseAction::Move`].
+    pub dest: Option<String>,
+}
+
+/// An item that can be chosen.
+struct Candidate {
+    stack: String,
+    item: Item,
+    /// What's shown and matched against, e.g. "work: Buy milk" across stacks.
+    text: String,
+}
+
+impl Choose {
+    /// Let the user choose items with a fuzzy finder, then act on them. Returns
+    /// what each effect did, in order; nothing if no items were chosen.
+    pub fn run(self, backend: &dyn Backend) -> Result<Vec<EffectResult>, SigiError> {
+        let candidates = self.candidates(backend)?;
+
+        let matcher = SkimMatcherV2::default();
+        let matched = matching(&matcher, &candidates, &self.query);
+        let chosen = if candidates.is_empty() || (self.select_one && matched.len() <= 1) {
+            matched
+        } else if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+            let msg = "Choosing items needs a terminal. (Try \"pick\" with numbers instead)";
+            return Err(io::Error::other(msg).into());
+        } else {
+            let mut terminal = ratatui::try_init()?;
+            let picker = Picker::new(&candidates, self.query.clone());
+            let chosen = picker.run(&mut terminal, &matcher, read_key);
+            ratatui::restore();
+            chosen?
+        };
+
+        self.apply(chosen.iter().map(|&i| &candidates[i]), backend)
+    }
+
+    /// Like [`Choose::run`], but always with the fuzzy finder, on any terminal
+    /// (like ratatui's `TestBackend`) and with key presses from `keys`. Running
+    /// out of keys cancels.
+    pub fn run_with_keys<T: ratatui::backend::Backend>(
+        self,
+        backend: &dyn Backend,
+        terminal: &mut Terminal<T>,
+        keys: impl IntoIterator<Item = KeyEvent>,
+    ) -> Result<Vec<EffectResult>, SigiError> {
+        let candidates = self.candidates(backend)?;
+        let picker = Picker::new(&candidates, self.query.clone());
+        let mut keys = keys.into_iter();
+        let chosen = picker.run(terminal, &SkimMatcherV2::default(), || Ok(keys.next()))?;
+        self.apply(chosen.iter().map(|&i| &candidates[i]), backend)
+    }
+
+    fn candidates(&self, backend: &dyn Backend) -> Result<Vec<Candidate>, SigiError> {
+        let stacks = if self.all_stacks {
+            match StackEffect::ListStacks.run(backend)? {
+                EffectResult::Stacks { stacks } => stacks
+                    .into_iter()
+                    .filter(|stack| !stack.ends_with(HISTORY_SUFFIX))
+                    .collect(),
+                _ => vec![],
+            }
+        } else {
+            vec![self.stack.clone()]
+        };
+
+        let mut candidates = vec![];
+        for stack in stacks {
+            let list = StackEffect::ListAll {
+                stack: stack.clone(),
+                view: View::default(),
+            };
+            let EffectResult::Listed { items, .. } = list.run(backend)? else {
+                continue;
+            };
+            candidates.extend(items.into_iter().map(|(_, item)| {
+                let text = if self.all_stacks {
+                    format!("{}: {}", stack, item.contents)
+                } else {
+                    item.contents.clone()
+                };
+                Candidate {
+                    stack: stack.clone(),
+                    item,
+                    text,
+                }
+            }));
+        }
+        Ok(candidates)
+    }
+
+    /// Run the action on the chosen items, in the order they were chosen.
+    /// It's all undone at once.
+    fn apply<'a>(
+        &self,
+        chosen: impl Iterator<Item = &'a Candidate>,
+        backend: &dyn Backend,
+    ) -> Result<Vec<EffectResult>, SigiError> {
+        if self.action == ChooseAction::Pick {
+            let mut by_stack: Vec<(String, Vec<ItemRef>)> = vec![];
+            for candidate in chosen {
+                let id = ItemRef::Id(candidate.item.id.clone());
+                match by_stack
+                    .iter_mut()
+                    .find(|(stack, _)| stack == &candidate.stack)
+                {
+                    Some((_, items)) => items.push(id),
+                    None => by_stack.push((candidate.stack.clone(), vec![id])),
+                }
+            }
+            let picks = by_stack
+                .into_iter()
+                .map(|(stack, items)| StackEffect::Pick { stack, items })
+                .collect();
+            return StackEffect::run_together(picks, backend);
+        }
+
+        // Everything else acts on the top of the stack, so each item is picked
+        // right before it's acted on. (Completing a recurring item puts its
+        // next occurrence on top)
+        let mut effects = vec![];
+        for candidate in chosen {
+            effects.push(StackEffect::Pick {
+                stack: candidate.stack.clone(),
+                items: vec![ItemRef::Id(candidate.item.id.clone())],
+            });
+            effects.push(self.effect_on_top(&candidate.stack));
+        }
+        let results = StackEffect::run_together(effects, backend)?;
+        // Only show what the action did.
+        Ok(results.into_iter().skip(1).step_by(2).collect())
+    }
+
+    fn effect_on_top(&self, stack: &str) -> StackEffect {
+        let stack = stack.to_string();
+        match self.action {
+            ChooseAction::Complete => StackEffect::Complete { stack },
+            ChooseAction::Delete => StackEffect::Delete { stack },
+            ChooseAction::Move => StackEffect::Move {
+                stack,
+                dest: self.dest.clone().unwrap_or_default(),
+            },
+            ChooseAction::Pick => unreachable!("[BUG] Picked items need nothing more."),
+        }
+    }
+}
+
+/// Print what was done to the chosen items.
+pub fn log_chosen(results: &[EffectResult], output: &OutputFormat) {
+    if results.is_empty() && output.is_nonquiet_for_humans() {
+        output.log(vec!["action", "item"], vec![vec!["Chosen", "NOTHING"]]);
+    }
+    results.iter().for_each(|result| result.log(output));
+}
+
+/// The candidates matching a query, best match first. An empty query matches
+/// everything, in stack order.
+fn matching(matcher: &SkimMatcherV2, candidates: &[Candidate], query: &str) -> Vec<usize> {
+    let mut scored: Vec<(i64, usize)> = candidates
+        .iter()
+        .enumerate()
+        .filter_map(|(i, candidate)| {
+            matcher
+                .fuzzy_match(&candidate.text, query)
+                .map(|score| (score, i))
+        })
+        .collect();
+    // Stable, so ties stay in stack order.
+    scored.sort_by_key(|(score, _)| -score);
+    scored.into_iter().map(|(_, i)| i).collect()
+}
+
+/// The state of the fuzzy finder on screen.
+struct Picker<'a> {
+    candidates: &'a [Candidate],
+    query: String,
+    /// Indexes into `candidates`, best match first.
+    matched: Vec<usize>,
+    /// Candidates marked with Tab, in the order they were marked.
+    marked: Vec<usize>,
+    state: ListState,
+}
+
+impl<'a> Picker<'a> {
+    fn new(candidates: &'a [Candidate], query: String) -> Self {
+        Picker {
+            candidates,
+            query,
+            matched: vec![],
+            marked: vec![],
+            state: ListState::default(),
+        }
+    }
+
+    /// Show the picker until the user accepts (Enter) or cancels (Esc or
+    /// Ctrl+C), with keys from `next_key`. Returns the marked candidates, or
+    /// the highlighted one if none are marked.
+    fn run<T: ratatui::backend::Backend>(
+        mut self,
+        terminal: &mut Terminal<T>,
+        matcher: &SkimMatcherV2,
+        mut next_key: impl FnMut() -> io::Result<Option<KeyEvent>>,
+    ) -> io::Result<Vec<usize>> {
+        self.refilter(matcher);
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            let Some(key) = next_key()? else {
+                return Ok(vec![]);
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+            match key.code {
+                KeyCode::Esc => return Ok(vec![]),
+                KeyCode::Char('c') if ctrl => return Ok(vec![]),
+                KeyCode::Enter => return Ok(self.chosen()),
+                KeyCode::Up => self.state.select_previous(),
+                KeyCode::Char('p') if ctrl => self.state.select_previous(),
+                KeyCode::Down => self.state.select_next(),
+                KeyCode::Char('n') if ctrl => self.state.select_next(),
+                KeyCode::Tab => {
+                    self.toggle_mark();
+                    self.state.select_next();
+                }
+                KeyCode::Backspace => {
+                    self.query.pop();
+                    self.refilter(matcher);
+                }
+                KeyCode::Char(c) if !ctrl => {
+                    self.query.push(c);
+                    self.refilter(matcher);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn refilter(&mut self, matcher: &SkimMatcherV2) {
+        self.matched = matching(matcher, self.candidates, &self.query);
+        let selected = if self.matched.is_empty() {
+            None
+        } else {
+            Some(0)
+        };
+        self.state.select(selected);
+    }
+
+    fn highlighted(&self) -> Option<usize> {
+        let i = self.state.selected()?;
+        self.matched.get(i).copied()
+    }
+
+    fn toggle_mark(&mut self) {
+        if let Some(i) = self.highlighted() {
+            match self.marked.iter().position(|&j| j == i) {
+                Some(at) => {
+                    self.marked.remove(at);
+                }
+                None => self.marked.push(i),
+            }
+        }
+    }
+
+    fn chosen(&self) -> Vec<usize> {
+        if self.marked.is_empty() {
+            self.highlighted().into_iter().collect()
+        } else {
+            self.marked.clone()
+        }
+    }
+
+    fn draw(&mut self, frame: &mut ratatui::Frame) {
+        let [input, status, list] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Min(0),
+        ])
+        .areas(frame.area());
+
+        frame.render_widget(Paragraph::new(format!("{}{}", PROMPT, self.query)), input);
+        let counts = format!(
+            "  {}/{} (Tab to mark, Enter to choose, Esc to cancel)",
+            self.matched.len(),
+            self.candidates.len(),
+        );
+        let dim = Style::default().add_modifier(Modifier::DIM);
+        frame.render_widget(Paragraph::new(counts).style(dim), status);
+
+        let lines = self.matched.iter().map(|&i| {
+            let mark = if self.marked.contains(&i) { "* " } else { "  " };
+            Line::from(format!("{}{}", mark, self.candidates[i].text))
+        });
+        let highlight = Style::default().add_modifier(Modifier::REVERSED);
+        let list_widget = List::new(lines).highlight_style(highlight);
+        frame.render_stateful_widget(list_widget, list, &mut self.state);
+
+        let cursor_x = input.x + (PROMPT.len() + self.query.chars().count()) as u16;
+        frame.set_cursor_position((cursor_x, input.y));
+    }
+}
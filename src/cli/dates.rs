@@ -0,0 +1,117 @@
+use chrono::{DateTime, Datelike, Days, Local, NaiveDate, TimeZone, Weekday};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Parse the start of a date range. A date without a time means the start of
+/// that day.
+pub fn parse_start(s: &str) -> Result<DateTime<Local>, String> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(dt.with_timezone(&Local));
+    }
+    parse_day(s).and_then(start_of_day)
+}
+
+/// Parse the end of a date range. A date without a time means the end of
+/// that day, so the whole day is included.
+pub fn parse_end(s: &str) -> Result<DateTime<Local>, String> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(dt.with_timezone(&Local));
+    }
+    end_of_day(parse_day(s)?)
+}
+
+fn end_of_day(day: NaiveDate) -> Result<DateTime<Local>, String> {
+    let next_day = day
+        .checked_add_days(Days::new(1))
+        .ok_or_else(|| format!("{} is too far in the future", day))?;
+    let end = start_of_day(next_day)? - chrono::Duration::nanoseconds(1);
+    Ok(end)
+}
+
+/// Parse a due date. Besides dates and times, this understands "today",
+/// "tomorrow", and days of the week like "fri" or "friday" (the next one,
+/// counting today). A day means the end of that day.
+pub fn parse_due(s: &str) -> Result<DateTime<Local>, String> {
+    let today = Local::now().date_naive();
+    let day = match s.to_lowercase().as_str() {
+        "today" => Some(today),
+        "tomorrow" => today.checked_add_days(Days::new(1)),
+        name => name.parse::<Weekday>().ok().and_then(|weekday| {
+            let days_ahead = weekday.days_since(today.weekday());
+            today.checked_add_days(Days::new(days_ahead.into()))
+        }),
+    };
+    match day {
+        Some(day) => end_of_day(day),
+        None => parse_end(s).map_err(|_| {
+            format!(
+                "expected a day like tomorrow, fri or 2026-11-01, or a time like 2026-11-01T09:00:00Z, but got {:?}",
+                s
+            )
+        }),
+    }
+}
+
+/// Parse when a snoozed item should come back. This understands durations
+/// from now like "30m", "2h", "3d" or "1w", "tomorrow", and days of the week
+/// like "mon" or "monday" (the next one, not counting today). A day means the
+/// start of that day.
+pub fn parse_snooze(s: &str) -> Result<DateTime<Local>, String> {
+    let now = Local::now();
+    if let Some(duration) = parse_duration(s) {
+        return now
+            .checked_add_signed(duration)
+            .ok_or_else(|| format!("{} is too far in the future", s));
+    }
+
+    let today = now.date_naive();
+    let day = match s.to_lowercase().as_str() {
+        "tomorrow" => today.checked_add_days(Days::new(1)),
+        name => name.parse::<Weekday>().ok().and_then(|weekday| {
+            let days_ahead = match weekday.days_since(today.weekday()) {
+                0 => 7,
+                n => n,
+            };
+            today.checked_add_days(Days::new(days_ahead.into()))
+        }),
+    };
+    match day {
+        Some(day) => start_of_day(day),
+        None => parse_start(s).map_err(|_| {
+            format!(
+                "expected a duration like 30m, 2h or 3d, a day like tomorrow, mon or 2026-11-01, or a time like 2026-11-01T09:00:00Z, but got {:?}",
+                s
+            )
+        }),
+    }
+}
+
+/// A number of minutes, hours, days or weeks, like "30m" or "2h".
+fn parse_duration(s: &str) -> Option<chrono::Duration> {
+    let unit = s.chars().last()?;
+    let n: i64 = s[..s.len() - unit.len_utf8()].parse().ok()?;
+    match unit {
+        'm' => chrono::Duration::try_minutes(n),
+        'h' => chrono::Duration::try_hours(n),
+        'd' => chrono::Duration::try_days(n),
+        'w' => chrono::Duration::try_weeks(n),
+        _ => None,
+    }
+}
+
+fn parse_day(s: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(s, DATE_FORMAT).map_err(|_| {
+        format!(
+            "expected a date like 2026-11-01, or a time like 2026-11-01T09:00:00Z, but got {:?}",
+            s
+        )
+    })
+}
+
+fn start_of_day(day: NaiveDate) -> Result<DateTime<Local>, String> {
+    let midnight = day.and_hms_opt(0, 0, 0).unwrap();
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .ok_or_else(|| format!("{} has no midnight in the local time zone", day))
+}
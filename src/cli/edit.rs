@@ -0,0 +1,105 @@
+use crate::data::Backend;
+use crate::effects::{EffectResult, ItemRef, StackEffect};
+use crate::error::SigiError;
+use std::fs::OpenOptions;
+use std::hash::{BuildHasher, RandomState};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::Instant;
+use std::{env, fs, io, process};
+
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Edit an item's contents and notes in the user's editor ($VISUAL or
+/// $EDITOR). The first line is the contents, and anything after a blank line
+/// is the notes, like a commit message. Saving an empty file changes nothing.
+pub fn edit_in_editor(
+    stack: String,
+    item: ItemRef,
+    backend: &dyn Backend,
+) -> Result<EffectResult, SigiError> {
+    let show = StackEffect::Show {
+        stack: stack.clone(),
+        item,
+    };
+    let item = match show.run(backend)? {
+        EffectResult::Shown {
+            item: Some((_, item)),
+            ..
+        } => item,
+        _ => return Ok(EffectResult::Edited { stack, item: None }),
+    };
+
+    let mut text = item.contents.clone();
+    if !item.notes.is_empty() {
+        text.push_str("\n\n");
+        text.push_str(&item.notes);
+    }
+    text.push('\n');
+
+    let edited = run_editor(&text)?;
+    let (contents, notes) = match edited.split_once('\n') {
+        Some((contents, notes)) => (contents.trim(), notes.trim()),
+        None => (edited.trim(), ""),
+    };
+    if contents.is_empty() {
+        return Ok(EffectResult::Edited { stack, item: None });
+    }
+
+    // By ID, since the item may have moved while the editor was open.
+    let edit = StackEffect::Edit {
+        stack,
+        item: ItemRef::Id(item.id),
+        contents: contents.to_string(),
+        notes: notes.to_string(),
+    };
+    edit.run(backend)
+}
+
+/// Open some text in the user's editor, and return it as saved.
+fn run_editor(text: &str) -> Result<String, SigiError> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| DEFAULT_EDITOR.to_string());
+    // Editors are often configured with arguments, like "code --wait".
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or(DEFAULT_EDITOR);
+
+    let path = write_temp_file(text)?;
+    let status = Command::new(program).args(words).arg(&path).status();
+    let edited = fs::read_to_string(&path);
+    fs::remove_file(&path)?;
+
+    let status = status?;
+    if !status.success() {
+        let msg = format!("Editor {:?} exited with {}", editor, status);
+        return Err(io::Error::other(msg).into());
+    }
+    Ok(edited?)
+}
+
+/// Write some text to a new file in the temp directory. The name is
+/// unpredictable and the file must not already exist, so nobody else can have
+/// put a file (or a symlink) there first.
+fn write_temp_file(text: &str) -> io::Result<PathBuf> {
+    loop {
+        let random = RandomState::new().hash_one(Instant::now());
+        let name = format!("sigi-edit-{}-{:x}.txt", process::id(), random);
+        let path = env::temp_dir().join(name);
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        match options.open(&path) {
+            Ok(mut file) => {
+                file.write_all(text.as_bytes())?;
+                return Ok(path);
+            }
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use super::Cli;
+use clap::CommandFactory;
+use clap_complete::Shell;
+use std::io::{self, Write};
+
+/// Completes stack names for -t/--stack (and its aliases) by asking sigi, then
+/// falls back to the generated completions.
+const BASH_STACK_NAMES: &str = r#"
+_sigi_with_stacks() {
+    local cur="${COMP_WORDS[COMP_CWORD]}"
+    case "${COMP_WORDS[COMP_CWORD-1]}" in
+        -t|--stack|--topic|--about|--namespace)
+            COMPREPLY=($(compgen -W "$(sigi --quiet list-stacks 2>/dev/null)" -- "${cur}"))
+            return 0
+            ;;
+    esac
+    _sigi "$@"
+}
+
+if [[ "${BASH_VERSINFO[0]}" -eq 4 && "${BASH_VERSINFO[1]}" -ge 4 || "${BASH_VERSINFO[0]}" -gt 4 ]]; then
+    complete -F _sigi_with_stacks -o nosort -o bashdefault -o default sigi
+else
+    complete -F _sigi_with_stacks -o bashdefault -o default sigi
+fi
+"#;
+
+const FISH_STACK_NAMES: &str = r#"
+complete -c sigi -s t -l stack -l topic -l about -l namespace -x -a "(sigi --quiet list-stacks 2>/dev/null)"
+"#;
+
+/// Completes stack names for zsh. The generated completions call this for the
+/// stack's value. (See [`print_completions`])
+const ZSH_STACK_NAMES: &str = r#"
+(( $+functions[_sigi_stacks] )) ||
+_sigi_stacks() {
+    local stacks; stacks=(${(f)"$(sigi --quiet list-stacks 2>/dev/null)"})
+    _describe -t stacks 'stack' stacks
+}
+"#;
+
+/// Completes stack names for elvish, wrapping the generated completer.
+const ELVISH_STACK_NAMES: &str = r#"
+var sigi-generated-completer = $edit:completion:arg-completer[sigi]
+set edit:completion:arg-completer[sigi] = {|@words|
+    if (and (> (count $words) 2) (has-value [-t --stack --topic --about --namespace] $words[-2])) {
+        sigi --quiet list-stacks 2>/dev/null | from-lines
+    } else {
+        $sigi-generated-completer $@words
+    }
+}
+"#;
+
+/// Completes stack names for PowerShell. This goes at the start of the
+/// generated completer's script block. (See [`print_completions`])
+const POWERSHELL_STACK_NAMES: &str = r#"
+    $previous = $commandAst.CommandElements |
+        Where-Object { $_.Extent.EndOffset -lt $cursorPosition } |
+        Select-Object -Last 1
+    if ($previous -and $previous.ToString() -in '-t', '--stack', '--topic', '--about', '--namespace') {
+        return sigi --quiet list-stacks 2>$null |
+            Where-Object { $_ -like "$wordToComplete*" } |
+            ForEach-Object { [CompletionResult]::new($_, $_, [CompletionResultType]::ParameterValue, $_) }
+    }
+"#;
+
+/// The first line of the generated PowerShell completer's script block.
+const POWERSHELL_PARAMS: &str = "    param($wordToComplete, $commandAst, $cursorPosition)\n";
+
+/// Print a completion script for the shell to standard output. Every shell
+/// also completes existing stack names.
+pub fn print_completions(shell: Shell) -> io::Result<()> {
+    let mut cli = Cli::command();
+    let name = cli.get_name().to_string();
+    let mut script = vec![];
+    clap_complete::generate(shell, &mut cli, name, &mut script);
+    let mut script = String::from_utf8_lossy(&script).into_owned();
+
+    match shell {
+        Shell::Bash => script.push_str(BASH_STACK_NAMES),
+        Shell::Fish => script.push_str(FISH_STACK_NAMES),
+        Shell::Zsh => {
+            // Values without a hint are completed with nothing in particular.
+            script = script.replace(":STACK:_default", ":STACK:_sigi_stacks");
+            // Defined before anything runs, but after the "#compdef" line.
+            let after_compdef = script.find('\n').map_or(0, |i| i + 1);
+            script.insert_str(after_compdef, ZSH_STACK_NAMES);
+        }
+        Shell::Elvish => script.push_str(ELVISH_STACK_NAMES),
+        Shell::PowerShell => {
+            let hooked = POWERSHELL_PARAMS.to_string() + POWERSHELL_STACK_NAMES;
+            script = script.replacen(POWERSHELL_PARAMS, &hooked, 1);
+        }
+        _ => (),
+    }
+    io::stdout().write_all(script.as_bytes())
+}
+
+/// Print the man page, in roff, to standard output.
+pub fn print_manpage() -> io::Result<()> {
+    let man = clap_mangen::Man::new(Cli::command());
+    let mut page = vec![];
+    man.render(&mut page)?;
+    // The text after the help is all about interactive mode, and is laid out
+    // by hand, so keep its lines as they are.
+    let page = String::from_utf8_lossy(&page)
+        .replace(".SH EXTRA\n", ".SH \"INTERACTIVE MODE\"\n.nf\n")
+        .replace(".SH VERSION\n", ".fi\n.SH VERSION\n");
+    io::stdout().write_all(page.as_bytes())
+}
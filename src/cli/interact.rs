@@ -1,9 +1,16 @@
 use super::*;
-use crate::effects::StackEffect;
+use crate::effects::{Amendment, EventFilter, ItemRef, RestoreQuery, Search, StackEffect, View};
 use crate::output::OutputFormat;
 use clap::CommandFactory;
+use rustyline::completion::Completer;
 use rustyline::error::ReadlineError;
-use rustyline::DefaultEditor;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{CompletionType, Config, Context, Editor, Helper};
+use std::io;
+use std::path::Path;
 use std::str::FromStr;
 
 const HUMAN_PROMPT: &str = "🌴 ▶ ";
@@ -12,7 +19,8 @@ pub const INTERACT_INSTRUCTIONS: &str = "INTERACTIVE MODE:
 
 Use subcommands in interactive mode directly. \
 No OPTIONS (flags) are understood in interactive mode. \
-The ; character can be used to separate commands.
+The ; character can be used to separate commands. \
+Tab completes commands, stack names, and words from items.
 
 The following additional commands are available:
     ?               Show the short version of \"help\"
@@ -38,6 +46,10 @@ No OPTIONS (flags) are understood in interactive mode.
 
 The ; character can be used to separate commands.
 
+Tab completes commands, stack names (after use, move, and move-all), and words
+from the stack's items. Lines typed in are kept beside the stacks, in
+\"interactive_history.txt\", for next time.
+
 In interactive mode, the following additional commands are available:
     ?
             Show the short version of \"help\"
@@ -50,10 +62,147 @@ In interactive mode, the following additional commands are available:
 
 // TODO: pagination/scrollback?
 // TODO: more comprehensive tests
-pub fn interact(original_stack: String, output: OutputFormat) {
+/// Words that start a command in interactive mode.
+const COMMAND_TERMS: [&[&str]; 37] = [
+    &AMEND_TERMS,
+    &COMPLETE_TERMS,
+    &COUNT_TERMS,
+    &DELETE_TERMS,
+    &DELETE_ALL_TERMS,
+    &DUE_TERMS,
+    &DUP_TERMS,
+    &EDIT_TERMS,
+    &FIND_TERMS,
+    &HISTORY_TERMS,
+    &HEAD_TERMS,
+    &IS_EMPTY_TERMS,
+    &LIST_TERMS,
+    &LIST_STACKS_TERMS,
+    &MINUS_ROT_TERMS,
+    &MOVE_TERMS,
+    &MOVE_ALL_TERMS,
+    &NEXT_TERMS,
+    &NIP_TERMS,
+    &OVER_TERMS,
+    &OVERDUE_TERMS,
+    &PEEK_TERMS,
+    &PICK_TERMS,
+    &PRUNE_TERMS,
+    &PUSH_TERMS,
+    &REDO_TERMS,
+    &RESTORE_TERMS,
+    &RETENTION_TERMS,
+    &ROLL_TERMS,
+    &ROT_TERMS,
+    &SHOW_TERMS,
+    &SNOOZE_TERMS,
+    &SWAP_TERMS,
+    &TAIL_TERMS,
+    &TUCK_TERMS,
+    &UNDO_TERMS,
+    &UNROLL_TERMS,
+];
+const INTERACT_TERMS: [&str; 8] = ["?", "help", "clear", "use", "stack", "exit", "quit", "q"];
+/// Commands that take a stack name.
+const STACK_NAME_TERMS: [&str; 4] = ["use", "stack", "move", "move-all"];
+
+/// Tab completion: command terms first, then stack names after the commands
+/// that take one, or words from the active stack's items after any other.
+struct Completions<'a> {
+    backend: &'a dyn Backend,
+    stack: String,
+}
+
+impl Completions<'_> {
+    fn candidates(&self, previous: &[&str]) -> Vec<String> {
+        let Some(term) = previous.first() else {
+            let terms = COMMAND_TERMS.iter().flat_map(|terms| terms.iter());
+            return terms
+                .chain(&INTERACT_TERMS)
+                .map(|s| s.to_string())
+                .collect();
+        };
+        // Completion shouldn't get in the way, so errors mean no candidates.
+        if STACK_NAME_TERMS.contains(&term.to_ascii_lowercase().as_str()) {
+            if previous.len() > 1 {
+                return vec![];
+            }
+            return self.backend.list_stacks().unwrap_or_default();
+        }
+        let items = self.backend.load(&self.stack).unwrap_or_default();
+        items
+            .iter()
+            .flat_map(|item| item.contents.split_whitespace())
+            .map(|word| word.to_string())
+            .collect()
+    }
+}
+
+impl Completer for Completions<'_> {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let line = &line[..pos];
+        // Only the last of several commands (separated by ;) matters.
+        let command = line.rsplit(';').next().unwrap_or(line);
+        let start = line
+            .rfind(|c: char| c.is_ascii_whitespace() || c == ';')
+            .map_or(0, |i| i + 1);
+        let word = &line[start..];
+        let previous = command[..command.len() - word.len()]
+            .split_ascii_whitespace()
+            .collect::<Vec<_>>();
+
+        let mut candidates = self
+            .candidates(&previous)
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(word))
+            .collect::<Vec<_>>();
+        candidates.sort();
+        candidates.dedup();
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for Completions<'_> {
+    type Hint = String;
+}
+
+impl Highlighter for Completions<'_> {}
+
+impl Validator for Completions<'_> {}
+
+impl Helper for Completions<'_> {}
+
+/// Run commands from the terminal (or standard input) until told to stop. Lines
+/// typed in are kept in `history`, if given, for next time.
+pub fn interact(
+    original_stack: String,
+    backend: &dyn Backend,
+    output: OutputFormat,
+    history: Option<&Path>,
+) {
     print_welcome_msg(output);
 
-    let mut rl = DefaultEditor::new().expect("Unable to create readline.");
+    // Like a shell: complete as far as possible, then list the options.
+    let config = Config::builder()
+        .completion_type(CompletionType::List)
+        .build();
+    let mut rl: Editor<Completions, DefaultHistory> =
+        Editor::with_config(config).expect("Unable to create readline.");
+    rl.set_helper(Some(Completions {
+        backend,
+        stack: original_stack.clone(),
+    }));
+    if let Some(history) = history {
+        // There's no history the first time.
+        let _ = rl.load_history(history);
+    }
     let prompt = if output.is_nonquiet_for_humans() {
         HUMAN_PROMPT
     } else {
@@ -62,7 +211,7 @@ pub fn interact(original_stack: String, output: OutputFormat) {
 
     let mut stack = original_stack;
 
-    loop {
+    'lines: loop {
         let line = rl.readline(prompt);
 
         if let Ok(line) = &line {
@@ -81,15 +230,25 @@ pub fn interact(original_stack: String, output: OutputFormat) {
                 ShortHelp => Cli::command().print_help().unwrap(),
                 LongHelp => Cli::command().print_long_help().unwrap(),
                 Clear => clearscreen::clear().expect("Failed to clear screen"),
-                DoEffect(effect) => effect.run(&DEFAULT_BACKEND, &output),
+                DoEffect(effect) => match effect.run(backend) {
+                    Ok(result) => result.log(&output),
+                    Err(err) => output.log_error(&err),
+                },
+                EditItem(item) => match edit::edit_in_editor(stack.clone(), item, backend) {
+                    Ok(result) => result.log(&output),
+                    Err(err) => output.log_error(&err),
+                },
                 UseStack(new_stack) => {
                     stack = new_stack;
+                    if let Some(completions) = rl.helper_mut() {
+                        completions.stack = stack.clone();
+                    }
                     output.log(vec!["update", "stack"], vec![vec!["Active stack", &stack]]);
                 }
                 NoContent => (),
                 Exit(reason) => {
                     print_goodbye_msg(&reason, output);
-                    return;
+                    break 'lines;
                 }
                 MissingArgument(msg) => {
                     output.log(
@@ -97,12 +256,15 @@ pub fn interact(original_stack: String, output: OutputFormat) {
                         vec![vec![&msg, "missing argument"]],
                     );
                 }
+                ParseError(msg) => {
+                    output.log(vec!["error", "message"], vec![vec!["parse error", &msg]]);
+                }
                 Error(msg) => {
                     output.log(
                         vec!["exit-message", "exit-reason"],
                         vec![vec!["Error"], vec![&msg]],
                     );
-                    return;
+                    break 'lines;
                 }
                 Unknown(term) => {
                     if output.is_nonquiet_for_humans() {
@@ -114,6 +276,23 @@ pub fn interact(original_stack: String, output: OutputFormat) {
             };
         }
     }
+
+    if let Some(history) = history {
+        if let Err(err) = save_history(&mut rl, history) {
+            output.log_error(&err);
+        }
+    }
+}
+
+fn save_history(
+    rl: &mut Editor<Completions, DefaultHistory>,
+    path: &Path,
+) -> Result<(), SigiError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    rl.save_history(path)
+        .map_err(|err| io::Error::other(err.to_string()).into())
 }
 
 fn print_welcome_msg(output: OutputFormat) {
@@ -139,10 +318,12 @@ enum InteractAction {
     LongHelp,
     Clear,
     DoEffect(StackEffect),
+    EditItem(ItemRef),
     UseStack(String),
     NoContent,
     Exit(String),
     MissingArgument(String),
+    ParseError(String),
     Error(String),
     Unknown(String),
 }
@@ -178,11 +359,15 @@ fn parse_line(line: String, stack: String) -> InteractAction {
         "help" => InteractAction::LongHelp,
         "clear" => InteractAction::Clear,
         "exit" | "quit" | "q" => InteractAction::Exit(term),
+        _ if EDIT_TERMS.contains(&term.as_str()) => {
+            let item = tokens.get(1).and_then(|s| ItemRef::from_str(s).ok());
+            InteractAction::EditItem(item.unwrap_or(ItemRef::Current))
+        }
         "use" | "stack" => match tokens.get(1) {
             Some(stack) => InteractAction::UseStack(stack.to_string()),
             None => InteractAction::MissingArgument("stack name".to_string()),
         },
-        _ => match parse_effect(tokens, stack) {
+        _ => match parse_effect(tokens, rest_of_line(&line), stack) {
             ParseEffectResult::Effect(effect) => InteractAction::DoEffect(effect),
             ParseEffectResult::NotEffect(parse_res) => parse_res,
             ParseEffectResult::Unknown => InteractAction::Unknown(term),
@@ -190,30 +375,72 @@ fn parse_line(line: String, stack: String) -> InteractAction {
     }
 }
 
+/// Everything after the first word of a line, as typed.
+fn rest_of_line(line: &str) -> &str {
+    let line = line.trim_ascii();
+    match line.find(|c: char| c.is_ascii_whitespace()) {
+        Some(end) => line[end..].trim_ascii_start(),
+        None => "",
+    }
+}
+
 enum ParseEffectResult {
     Effect(StackEffect),
     NotEffect(InteractAction),
     Unknown,
 }
 
-fn parse_effect(tokens: Vec<&str>, stack: String) -> ParseEffectResult {
+fn parse_effect(tokens: Vec<&str>, rest: &str, stack: String) -> ParseEffectResult {
     let term = tokens.first().unwrap_or(&"");
 
-    let parse_n = || {
+    let parse_n_or = |default| {
         tokens
             .get(1)
             .and_then(|s| usize::from_str(s).ok())
-            .unwrap_or(DEFAULT_SHORT_LIST_LIMIT)
+            .unwrap_or(default)
+    };
+    let parse_n = || parse_n_or(DEFAULT_SHORT_LIST_LIMIT);
+    // A required number, like "roll 3".
+    let parse_position = || tokens.get(1).and_then(|s| usize::from_str(s).ok());
+    // Tags to filter by, like "list +review".
+    let parse_tags = || {
+        tokens[1..]
+            .iter()
+            .filter(|s| s.starts_with(['+', '#']))
+            .filter_map(|s| data::parse_tag(s))
+            .collect()
+    };
+    // Which items to list, like "list +review --include-snoozed".
+    let parse_view = || View {
+        tags: parse_tags(),
+        snoozed: tokens[1..].contains(&"--include-snoozed"),
     };
 
     use ParseEffectResult::*;
     use StackEffect::*;
 
+    if AMEND_TERMS.contains(term) {
+        // Like the command: "amend [item] <new text>", or "amend [item] s/old/new/".
+        let (item, text) = match tokens.get(1).map(|s| ItemRef::from_str(s)) {
+            Some(Ok(item)) if tokens.len() > 2 => (item, rest_of_line(rest)),
+            _ => (ItemRef::Current, rest),
+        };
+        if text.is_empty() {
+            return NotEffect(InteractAction::MissingArgument("new text".to_string()));
+        }
+        let amendment = Amendment::parse(text);
+        return Effect(Amend {
+            stack,
+            item,
+            amendment,
+        });
+    }
     if COMPLETE_TERMS.contains(term) {
         return Effect(Complete { stack });
     }
     if COUNT_TERMS.contains(term) {
-        return Effect(Count { stack });
+        let tags = parse_tags();
+        return Effect(Count { stack, tags });
     }
     if DELETE_TERMS.contains(term) {
         return Effect(Delete { stack });
@@ -221,15 +448,54 @@ fn parse_effect(tokens: Vec<&str>, stack: String) -> ParseEffectResult {
     if DELETE_ALL_TERMS.contains(term) {
         return Effect(DeleteAll { stack });
     }
+    if DUE_TERMS.contains(term) {
+        return Effect(Due { overdue: false });
+    }
+    if DUP_TERMS.contains(term) {
+        return Effect(Dup { stack });
+    }
+    if FIND_TERMS.contains(term) {
+        // Like "find --all milk", ignoring case like other searches here.
+        let flags = ["--all", "--history", "--regex"];
+        let text = tokens[1..]
+            .iter()
+            .filter(|s| !flags.contains(s))
+            .copied()
+            .collect::<Vec<_>>();
+        if text.is_empty() {
+            return NotEffect(InteractAction::MissingArgument("text to find".to_string()));
+        }
+        let regex = tokens.contains(&"--regex");
+        let pattern = match search_pattern(&text.join(" "), true, regex) {
+            Ok(pattern) => pattern,
+            Err(err) => return NotEffect(InteractAction::ParseError(err.to_string())),
+        };
+        let search = Search {
+            pattern,
+            all_stacks: tokens.contains(&"--all"),
+            history: tokens.contains(&"--history"),
+        };
+        return Effect(Find { stack, search });
+    }
     if HEAD_TERMS.contains(term) {
         let n = parse_n();
-        return Effect(Head { stack, n });
+        let view = parse_view();
+        return Effect(Head { stack, n, view });
+    }
+    if HISTORY_TERMS.contains(term) {
+        // Any arguments are events to show, like "history completed deleted".
+        let filter = EventFilter {
+            events: tokens[1..].iter().map(|s| s.to_string()).collect(),
+            ..EventFilter::default()
+        };
+        return Effect(History { stack, filter });
     }
     if IS_EMPTY_TERMS.contains(term) {
         return Effect(IsEmpty { stack });
     }
     if LIST_TERMS.contains(term) {
-        return Effect(ListAll { stack });
+        let view = parse_view();
+        return Effect(ListAll { stack, view });
     }
     if LIST_STACKS_TERMS.contains(term) {
         return Effect(ListStacks);
@@ -263,30 +529,110 @@ fn parse_effect(tokens: Vec<&str>, stack: String) -> ParseEffectResult {
     if NEXT_TERMS.contains(term) {
         return Effect(Next { stack });
     }
+    if NIP_TERMS.contains(term) {
+        return Effect(Nip { stack });
+    }
+    if OVER_TERMS.contains(term) {
+        return Effect(Over { stack });
+    }
+    if OVERDUE_TERMS.contains(term) {
+        return Effect(Due { overdue: true });
+    }
     if PEEK_TERMS.contains(term) {
-        return Effect(Peek { stack });
+        let view = View {
+            tags: parse_tags(),
+            snoozed: false,
+        };
+        return Effect(Peek { stack, view });
     }
     if PICK_TERMS.contains(term) {
-        let indices = tokens
+        let items = tokens[1..]
             .iter()
-            .filter_map(|s| usize::from_str(s).ok())
+            .filter_map(|s| ItemRef::from_str(s).ok())
             .collect();
-        return Effect(Pick { stack, indices });
+        return Effect(Pick { stack, items });
+    }
+    if PRUNE_TERMS.contains(term) {
+        let dry_run = tokens[1..]
+            .iter()
+            .any(|s| *s == "--dry-run" || *s == "dry-run");
+        return Effect(Prune { stack, dry_run });
     }
     if PUSH_TERMS.contains(term) {
-        // FIXME: This is convenient, but normalizes whitespace. (E.g. multiple spaces always collapsed, tabs to spaces, etc)
-        let content = tokens[1..].join(" ");
-        return Effect(Push { stack, content });
+        let item = Item::new(rest);
+        return Effect(Push { stack, item });
+    }
+    if REDO_TERMS.contains(term) {
+        let n = parse_n_or(1);
+        return Effect(Redo { n });
+    }
+    if RESTORE_TERMS.contains(term) {
+        // Positions and IDs are items in the history, and anything else is searched for.
+        let (refs, words): (Vec<&str>, Vec<&str>) = tokens[1..]
+            .iter()
+            .partition(|s| ItemRef::from_str(s).is_ok());
+        let items = refs
+            .iter()
+            .filter_map(|s| ItemRef::from_str(s).ok())
+            .collect();
+        let search = if words.is_empty() {
+            None
+        } else {
+            Some(words.join(" "))
+        };
+        let query = RestoreQuery { items, search };
+        return Effect(Restore { stack, query });
+    }
+    if RETENTION_TERMS.contains(term) {
+        return Effect(ShowRetention { stack });
+    }
+    if ROLL_TERMS.contains(term) {
+        return match parse_position() {
+            Some(n) => Effect(Roll { stack, n }),
+            None => NotEffect(InteractAction::MissingArgument("a number".to_string())),
+        };
     }
     if ROT_TERMS.contains(term) {
         return Effect(Rot { stack });
     }
+    if SHOW_TERMS.contains(term) {
+        let item = tokens.get(1).and_then(|s| ItemRef::from_str(s).ok());
+        let item = item.unwrap_or(ItemRef::Current);
+        return Effect(Show { stack, item });
+    }
+    if SNOOZE_TERMS.contains(term) {
+        match tokens.get(1).and_then(|s| dates::parse_snooze(s).ok()) {
+            Some(at) => return Effect(Snooze { stack, at }),
+            None => {
+                return NotEffect(InteractAction::MissingArgument(
+                    "how long to snooze (e.g. 2h or tomorrow)".to_string(),
+                ));
+            }
+        };
+    }
     if SWAP_TERMS.contains(term) {
         return Effect(Swap { stack });
     }
     if TAIL_TERMS.contains(term) {
         let n = parse_n();
-        return Effect(Tail { stack, n });
+        let view = parse_view();
+        return Effect(Tail { stack, n, view });
+    }
+    if TUCK_TERMS.contains(term) {
+        return Effect(Tuck { stack });
+    }
+    if UNDO_TERMS.contains(term) {
+        let n = parse_n_or(1);
+        return Effect(Undo { n });
+    }
+    if UNROLL_TERMS.contains(term) {
+        return match parse_position() {
+            Some(n) => Effect(Unroll { stack, n }),
+            None => NotEffect(InteractAction::MissingArgument("a number".to_string())),
+        };
+    }
+    if MINUS_ROT_TERMS.contains(term) {
+        return Effect(MinusRot { stack });
     }
 
     Unknown
@@ -0,0 +1,468 @@
+use crate::data::{Backend, Item};
+use crate::effects::{EffectResult, ItemRef, JournalEntry, StackEffect, View, HISTORY_SUFFIX};
+use crate::error::SigiError;
+use chrono::Local;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListState, Paragraph};
+use ratatui::{Frame, Terminal};
+use std::io::{self, IsTerminal};
+
+const ITEM_KEYS: &str = "a push  c complete  d delete  m move  p pick  J/K move down/up  \
+                         s swap  r rot  n next  u undo  ^R redo  Tab stacks  q quit";
+const STACK_KEYS: &str = "Enter use stack  Tab items  q quit";
+
+/// Run the full-screen terminal UI until the user quits. Every change is made
+/// by running a [`StackEffect`], just like the other commands.
+pub fn run_tui(stack: String, backend: &dyn Backend) -> Result<(), SigiError> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        let msg = "The full-screen UI needs a terminal. (Try \"interactive\" instead)";
+        return Err(io::Error::other(msg).into());
+    }
+
+    let mut app = App::new(stack, backend);
+    app.refresh();
+    let mut terminal = ratatui::try_init()?;
+    let result = app.run(&mut terminal, read_key);
+    ratatui::restore();
+    Ok(result?)
+}
+
+/// Run the UI on any terminal, like ratatui's `TestBackend`, with key presses
+/// from `keys` instead of the keyboard. Stops when a key quits or there are
+/// no keys left.
+pub fn run_tui_with_keys<T: ratatui::backend::Backend>(
+    stack: String,
+    backend: &dyn Backend,
+    terminal: &mut Terminal<T>,
+    keys: impl IntoIterator<Item = KeyEvent>,
+) -> Result<(), SigiError> {
+    let mut app = App::new(stack, backend);
+    app.refresh();
+    let mut keys = keys.into_iter();
+    app.run(terminal, || Ok(keys.next()))?;
+    Ok(())
+}
+
+/// Wait for the next key event from the keyboard.
+pub(super) fn read_key() -> io::Result<Option<KeyEvent>> {
+    loop {
+        if let Event::Key(key) = event::read()? {
+            return Ok(Some(key));
+        }
+    }
+}
+
+/// Which panel keys go to.
+#[derive(PartialEq, Eq)]
+enum Focus {
+    Items,
+    Stacks,
+}
+
+/// What typed text is for.
+enum Prompt {
+    Push,
+    Move,
+}
+
+impl Prompt {
+    fn label(&self) -> &'static str {
+        match self {
+            Prompt::Push => "Push: ",
+            Prompt::Move => "Move to stack: ",
+        }
+    }
+}
+
+struct App<'a> {
+    backend: &'a dyn Backend,
+    stack: String,
+    /// Every stack except history stacks, sorted.
+    stacks: Vec<String>,
+    items: Vec<Item>,
+    history: Vec<Item>,
+    focus: Focus,
+    item_state: ListState,
+    stack_state: ListState,
+    /// Text being typed, and what it's for.
+    input: Option<(Prompt, String)>,
+    /// What the last effect did, or what went wrong.
+    message: String,
+}
+
+impl<'a> App<'a> {
+    fn new(stack: String, backend: &'a dyn Backend) -> Self {
+        App {
+            backend,
+            stack,
+            stacks: vec![],
+            items: vec![],
+            history: vec![],
+            focus: Focus::Items,
+            item_state: ListState::default().with_selected(Some(0)),
+            stack_state: ListState::default(),
+            input: None,
+            message: String::new(),
+        }
+    }
+
+    /// Draw and handle keys from `next_key` until one quits or there are none
+    /// left.
+    fn run<T: ratatui::backend::Backend>(
+        &mut self,
+        terminal: &mut Terminal<T>,
+        mut next_key: impl FnMut() -> io::Result<Option<KeyEvent>>,
+    ) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            let Some(key) = next_key()? else {
+                return Ok(());
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let quit = match (&self.input, &self.focus) {
+                (Some(_), _) => self.handle_input_key(key),
+                (None, Focus::Items) => self.handle_item_key(key),
+                (None, Focus::Stacks) => self.handle_stack_key(key),
+            };
+            if quit {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Handle a key while text is being typed. Never quits.
+    fn handle_input_key(&mut self, key: KeyEvent) -> bool {
+        let Some((prompt, text)) = &mut self.input else {
+            return false;
+        };
+        match key.code {
+            KeyCode::Esc => self.input = None,
+            KeyCode::Backspace => {
+                text.pop();
+            }
+            KeyCode::Char(c) => text.push(c),
+            KeyCode::Enter => {
+                let text = text.trim().to_string();
+                let stack = self.stack.clone();
+                let effect = match prompt {
+                    _ if text.is_empty() => None,
+                    Prompt::Push => Some(StackEffect::Push {
+                        stack,
+                        item: Item::new(&text),
+                    }),
+                    Prompt::Move => Some(StackEffect::Move { stack, dest: text }),
+                };
+                let moving = matches!(prompt, Prompt::Move);
+                self.input = None;
+                match effect {
+                    Some(effect) if moving => self.run_on_selected(effect),
+                    Some(effect) => {
+                        self.run_effect(effect);
+                        self.item_state.select_first();
+                    }
+                    None => (),
+                }
+            }
+            _ => (),
+        }
+        false
+    }
+
+    /// Handle a key for the active stack's items. Returns true to quit.
+    fn handle_item_key(&mut self, key: KeyEvent) -> bool {
+        let stack = self.stack.clone();
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return true,
+            KeyCode::Char('c') if ctrl => return true,
+            KeyCode::Char('r') if ctrl => self.run_effect(StackEffect::Redo { n: 1 }),
+            KeyCode::Tab => self.focus = Focus::Stacks,
+            KeyCode::Up | KeyCode::Char('k') => self.item_state.select_previous(),
+            KeyCode::Down | KeyCode::Char('j') => self.item_state.select_next(),
+            KeyCode::Char('K') => {
+                self.move_selected_up();
+            }
+            KeyCode::Char('J') => {
+                // Moving an item down is moving the one under it up.
+                self.item_state.select_next();
+                if self.move_selected_up() {
+                    self.item_state.select_next();
+                } else {
+                    self.item_state.select_previous();
+                }
+            }
+            KeyCode::Char('a') => self.input = Some((Prompt::Push, String::new())),
+            KeyCode::Char('m') => self.input = Some((Prompt::Move, String::new())),
+            KeyCode::Char('c') => self.run_on_selected(StackEffect::Complete { stack }),
+            KeyCode::Char('d') => self.run_on_selected(StackEffect::Delete { stack }),
+            KeyCode::Char('p') | KeyCode::Enter => {
+                if let Some(id) = self.selected_id() {
+                    let items = vec![ItemRef::Id(id)];
+                    self.run_effect(StackEffect::Pick { stack, items });
+                    self.item_state.select_first();
+                }
+            }
+            KeyCode::Char('s') => self.run_effect(StackEffect::Swap { stack }),
+            KeyCode::Char('r') => self.run_effect(StackEffect::Rot { stack }),
+            KeyCode::Char('n') => self.run_effect(StackEffect::Next { stack }),
+            KeyCode::Char('u') => self.run_effect(StackEffect::Undo { n: 1 }),
+            _ => (),
+        }
+        false
+    }
+
+    /// Handle a key for the list of stacks. Returns true to quit.
+    fn handle_stack_key(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return true,
+            KeyCode::Char('c') if ctrl => return true,
+            KeyCode::Tab => self.focus = Focus::Items,
+            KeyCode::Up | KeyCode::Char('k') => self.stack_state.select_previous(),
+            KeyCode::Down | KeyCode::Char('j') => self.stack_state.select_next(),
+            KeyCode::Enter => {
+                let selected = self.stack_state.selected();
+                if let Some(stack) = selected.and_then(|i| self.stacks.get(i)) {
+                    self.stack = stack.clone();
+                    self.focus = Focus::Items;
+                    self.message = format!("Active stack: {}", self.stack);
+                    self.item_state.select_first();
+                    self.refresh();
+                }
+            }
+            _ => (),
+        }
+        false
+    }
+
+    fn selected(&self) -> Option<usize> {
+        let i = self.item_state.selected()?;
+        (i < self.items.len()).then_some(i)
+    }
+
+    fn selected_id(&self) -> Option<String> {
+        self.selected().map(|i| self.items[i].id.clone())
+    }
+
+    /// Run an effect that acts on the current item against the selected item
+    /// instead, by picking it first. It's picked by ID even if it was shown on
+    /// top, in case the stack changed since. Both are undone together.
+    fn run_on_selected(&mut self, effect: StackEffect) {
+        if let Some(id) = self.selected_id() {
+            let pick = StackEffect::Pick {
+                stack: self.stack.clone(),
+                items: vec![ItemRef::Id(id)],
+            };
+            self.run_effects(vec![pick, effect]);
+        }
+    }
+
+    /// Swap the selected item with the one above it, and keep it selected.
+    /// Returns whether it moved.
+    fn move_selected_up(&mut self) -> bool {
+        let Some(i) = self.selected().filter(|i| *i > 0) else {
+            return false;
+        };
+        // Everything above the one it swaps with stays put.
+        let mut items: Vec<ItemRef> = self.items[..i - 1]
+            .iter()
+            .map(|item| ItemRef::Id(item.id.clone()))
+            .collect();
+        items.push(ItemRef::Id(self.items[i].id.clone()));
+        let pick = StackEffect::Pick {
+            stack: self.stack.clone(),
+            items,
+        };
+        let moved = self.try_effect(pick);
+        if moved {
+            self.item_state.select(Some(i - 1));
+        }
+        moved
+    }
+
+    /// Run an effect, then show what it did.
+    fn run_effect(&mut self, effect: StackEffect) {
+        self.try_effect(effect);
+    }
+
+    /// Like [`App::run_effect`], returning whether it succeeded.
+    fn try_effect(&mut self, effect: StackEffect) -> bool {
+        let succeeded = match effect.run(self.backend) {
+            Ok(result) => {
+                self.message = describe(&result);
+                true
+            }
+            Err(err) => {
+                self.message = format!("Error: {}", err);
+                false
+            }
+        };
+        self.refresh();
+        succeeded
+    }
+
+    /// Run effects as one (see [`StackEffect::run_together`]), then show what
+    /// the last one did.
+    fn run_effects(&mut self, effects: Vec<StackEffect>) {
+        match StackEffect::run_together(effects, self.backend) {
+            Ok(results) => {
+                if let Some(result) = results.last() {
+                    self.message = describe(result);
+                }
+            }
+            Err(err) => self.message = format!("Error: {}", err),
+        }
+        self.refresh();
+    }
+
+    /// Load the stacks and items to show.
+    fn refresh(&mut self) {
+        if let Err(err) = self.load() {
+            self.message = format!("Error: {}", err);
+        }
+    }
+
+    fn load(&mut self) -> Result<(), SigiError> {
+        if let EffectResult::Stacks { stacks } = StackEffect::ListStacks.run(self.backend)? {
+            self.stacks = stacks
+                .into_iter()
+                .filter(|stack| !stack.ends_with(HISTORY_SUFFIX))
+                .collect();
+        }
+        if !self.stacks.contains(&self.stack) {
+            self.stacks.push(self.stack.clone());
+            self.stacks.sort();
+        }
+        let active = self.stacks.iter().position(|stack| stack == &self.stack);
+        self.stack_state.select(active);
+
+        self.items = self.list(self.stack.clone())?;
+        self.history = self.list(self.stack.clone() + HISTORY_SUFFIX)?;
+        Ok(())
+    }
+
+    fn list(&self, stack: String) -> Result<Vec<Item>, SigiError> {
+        let view = View::default();
+        match (StackEffect::ListAll { stack, view }).run(self.backend)? {
+            EffectResult::Listed { items, .. } => {
+                Ok(items.into_iter().map(|(_, item)| item).collect())
+            }
+            _ => Ok(vec![]),
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status, help] = Layout::vertical([
+            Constraint::Min(0),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [sidebar, stack_area] =
+            Layout::horizontal([Constraint::Length(24), Constraint::Min(0)]).areas(main);
+        let [items_area, history_area] =
+            Layout::vertical([Constraint::Percentage(65), Constraint::Percentage(35)])
+                .areas(stack_area);
+
+        let highlight = Style::default().add_modifier(Modifier::REVERSED);
+
+        let stacks = List::new(self.stacks.iter().map(String::as_str))
+            .block(self.block("Stacks".to_string(), Focus::Stacks))
+            .highlight_style(highlight);
+        frame.render_stateful_widget(stacks, sidebar, &mut self.stack_state);
+
+        let now = Local::now();
+        let items = self.items.iter().enumerate().map(|(i, item)| {
+            let position = if i == 0 {
+                "Now".to_string()
+            } else {
+                i.to_string()
+            };
+            let overdue = if item.is_overdue(now) {
+                " (OVERDUE)"
+            } else {
+                ""
+            };
+            Line::from(format!("{:>3}: {}{}", position, item.contents, overdue))
+        });
+        let title = format!("{} ({})", self.stack, self.items.len());
+        let items = List::new(items)
+            .block(self.block(title, Focus::Items))
+            .highlight_style(highlight);
+        frame.render_stateful_widget(items, items_area, &mut self.item_state);
+
+        // The most recent event says what happened to each item.
+        let history = self.history.iter().map(|item| {
+            let event = item.history.last().map(|event| event.name.as_str());
+            Line::from(format!("{}: {}", event.unwrap_or("?"), item.contents))
+        });
+        let title = self.stack.clone() + HISTORY_SUFFIX;
+        let dim = Style::default().add_modifier(Modifier::DIM);
+        let history = List::new(history).block(Block::bordered().title(title).style(dim));
+        frame.render_widget(history, history_area);
+
+        self.draw_status(frame, status);
+        let keys = match self.focus {
+            Focus::Items => ITEM_KEYS,
+            Focus::Stacks => STACK_KEYS,
+        };
+        frame.render_widget(Paragraph::new(keys).style(dim), help);
+    }
+
+    fn draw_status(&self, frame: &mut Frame, area: Rect) {
+        match &self.input {
+            Some((prompt, text)) => {
+                let label = prompt.label();
+                frame.render_widget(Paragraph::new(format!("{}{}", label, text)), area);
+                let x = area.x + (label.len() + text.chars().count()) as u16;
+                frame.set_cursor_position((x, area.y));
+            }
+            None => frame.render_widget(Paragraph::new(self.message.as_str()), area),
+        }
+    }
+
+    fn block(&self, title: String, panel: Focus) -> Block<'static> {
+        let block = Block::bordered().title(title);
+        if self.focus == panel {
+            block.border_style(Style::default().add_modifier(Modifier::BOLD))
+        } else {
+            block
+        }
+    }
+}
+
+/// A one-line summary of what an effect did.
+fn describe(result: &EffectResult) -> String {
+    let contents = |item: Option<&Item>| {
+        item.map(|item| item.contents.clone())
+            .unwrap_or_else(|| "NOTHING".to_string())
+    };
+    let effects = |entries: &[JournalEntry]| {
+        let names: Vec<&str> = entries.iter().map(|entry| entry.effect.as_str()).collect();
+        if names.is_empty() {
+            "NOTHING".to_string()
+        } else {
+            names.join(", ")
+        }
+    };
+
+    use EffectResult::*;
+    match result {
+        Pushed { item, .. } => format!("Created: {}", item.contents),
+        Completed { item, .. } => format!("Completed: {}", contents(item.as_ref())),
+        Deleted { item, .. } => format!("Deleted: {}", contents(item.as_ref())),
+        Moved { dest, item, .. } => format!("Moved to {}: {}", dest, contents(item.as_ref())),
+        Reordered { top, .. } if top.is_empty() => "Nothing changed".to_string(),
+        Reordered { top, .. } => format!("Now: {}", contents(top.first())),
+        Cycled { now, .. } => format!("Now: {}", contents(now.as_ref())),
+        Undone(entries) => format!("Undone: {}", effects(entries)),
+        Redone(entries) => format!("Redone: {}", effects(entries)),
+        _ => String::new(),
+    }
+}
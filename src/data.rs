@@ -1,188 +1,574 @@
-use std::error::Error;
-use std::io::ErrorKind;
-use std::{env, fs, path::PathBuf};
+use crate::error::SigiError;
+use std::fs::{File, TryLockError};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use std::{env, fs, io, thread};
 
 use directories::ProjectDirs;
 
-// TODO: Alternate backends:
-//       - Redis
-//       - SQLite
-//       - The existing version (JSON via Serde)
-// TODO: Configurable data location?
+mod homedir;
+pub use homedir::HomeDir;
+
+#[cfg(feature = "redis")]
+mod redis;
+#[cfg(feature = "redis")]
+pub use self::redis::Redis;
+
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "sqlite")]
+pub use sqlite::Sqlite;
+
 // TODO: Allow an idea of "stack of stacks"
 
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Datelike, Days, Local, Weekday};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+pub type ItemHistory = Vec<ItemEvent>;
 
-type ItemHistory = Vec<(String, DateTime<Local>)>;
+/// Something that happened to an item, like being created or completed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(from = "StoredEvent", into = "StoredEvent")]
+pub struct ItemEvent {
+    /// What happened, e.g. "created".
+    pub name: String,
+    pub at: DateTime<Local>,
+    /// More about what happened, like an edited item's previous contents.
+    pub detail: Option<String>,
+}
+
+impl ItemEvent {
+    /// An event happening now.
+    pub fn now(name: &str) -> Self {
+        ItemEvent::new(name, Local::now())
+    }
+
+    pub fn new(name: &str, at: DateTime<Local>) -> Self {
+        ItemEvent {
+            name: name.to_string(),
+            at,
+            detail: None,
+        }
+    }
+}
+
+/// An event as saved: `[name, at]`, or `[name, at, detail]` if it has a detail.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum StoredEvent {
+    Plain(String, DateTime<Local>),
+    Detailed(String, DateTime<Local>, String),
+}
+
+impl From<StoredEvent> for ItemEvent {
+    fn from(stored: StoredEvent) -> Self {
+        match stored {
+            StoredEvent::Plain(name, at) => ItemEvent {
+                name,
+                at,
+                detail: None,
+            },
+            StoredEvent::Detailed(name, at, detail) => ItemEvent {
+                name,
+                at,
+                detail: Some(detail),
+            },
+        }
+    }
+}
+
+impl From<ItemEvent> for StoredEvent {
+    fn from(event: ItemEvent) -> Self {
+        match event.detail {
+            None => StoredEvent::Plain(event.name, event.at),
+            Some(detail) => StoredEvent::Detailed(event.name, event.at, detail),
+        }
+    }
+}
 
 /// A single stack item.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(from = "StoredItem")]
 pub struct Item {
+    /// A short identifier that doesn't change as the item moves around, unlike
+    /// its position. (See [`Item::id_for`])
+    pub id: String,
     // TODO: Update from "name" to "contents"?
     pub contents: String,
+    /// A longer, possibly multi-line, body.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+    /// Tags written in the contents, like `+review` or `#oncall`. (See [`tags_in`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Tags,
+    /// When the item should be done by, if ever.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<DateTime<Local>>,
+    /// Hidden from listings until this time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snoozed_until: Option<DateTime<Local>>,
+    /// How often the item comes back after it's completed, if it does.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<Recurrence>,
+    /// For recurring items, the ID of the first item in the series.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub series: Option<String>,
     pub history: ItemHistory,
 }
 
+/// An item as saved. Items saved before IDs or tags existed get them when loaded.
+#[derive(Deserialize)]
+struct StoredItem {
+    #[serde(default)]
+    id: Option<String>,
+    contents: String,
+    #[serde(default)]
+    notes: String,
+    #[serde(default)]
+    tags: Option<Tags>,
+    #[serde(default)]
+    due: Option<DateTime<Local>>,
+    #[serde(default)]
+    snoozed_until: Option<DateTime<Local>>,
+    #[serde(default)]
+    recurrence: Option<Recurrence>,
+    #[serde(default)]
+    series: Option<String>,
+    history: ItemHistory,
+}
+
+impl From<StoredItem> for Item {
+    fn from(stored: StoredItem) -> Self {
+        let mut item = Item::from_history(stored.contents, stored.history);
+        if let Some(id) = stored.id {
+            item.id = id;
+        }
+        if let Some(tags) = stored.tags {
+            item.tags = tags;
+        }
+        item.notes = stored.notes;
+        item.due = stored.due;
+        item.snoozed_until = stored.snoozed_until;
+        item.recurrence = stored.recurrence;
+        item.series = stored.series;
+        item
+    }
+}
+
+/// Letters used in item IDs. There are no digits, so an ID is never mistaken
+/// for a position, and no vowels, so it's rarely mistaken for a word.
+const ID_ALPHABET: &[u8] = b"bcdfghjkmnpqrstvwxz";
+
+const ID_LENGTH: usize = 6;
+
 impl Item {
     pub fn new(contents: &str) -> Self {
+        Item::from_history(contents.to_string(), vec![ItemEvent::now("created")])
+    }
+
+    /// An item with an existing history, and the ID and tags it would have
+    /// been given when it was created.
+    pub fn from_history(contents: String, history: ItemHistory) -> Self {
         Item {
-            contents: contents.to_string(),
-            history: vec![("created".to_string(), Local::now())],
+            id: Item::id_for(&contents, &history),
+            tags: tags_in(&contents),
+            notes: String::new(),
+            due: None,
+            snoozed_until: None,
+            recurrence: None,
+            series: None,
+            contents,
+            history,
         }
     }
 
+    /// A new item with the same contents, notes, due date and recurrence, but
+    /// its own ID and history.
+    pub fn duplicate(&self) -> Self {
+        let mut copy = Item::new(&self.contents);
+        copy.notes = self.notes.clone();
+        copy.due = self.due;
+        copy.recurrence = self.recurrence.clone();
+        copy
+    }
+
+    /// Whether the item is past its due date.
+    pub fn is_overdue(&self, now: DateTime<Local>) -> bool {
+        self.due.is_some_and(|due| due < now)
+    }
+
+    /// Whether the item is hidden until later.
+    pub fn is_snoozed(&self, now: DateTime<Local>) -> bool {
+        self.snoozed_until.is_some_and(|until| until > now)
+    }
+
+    /// Whether the item has every one of these tags.
+    pub fn has_tags(&self, tags: &[String]) -> bool {
+        tags.iter().all(|tag| self.tags.contains(tag))
+    }
+
+    /// The ID for an item: a hash of its contents and when it was created.
+    /// The same item always gets the same ID, even if it's generated again
+    /// for an item saved before IDs existed.
+    pub fn id_for(contents: &str, history: &ItemHistory) -> String {
+        // 64-bit FNV-1a
+        let mut hash: u64 = 0xcbf29ce484222325;
+        // In UTC, so the ID doesn't depend on the local time zone.
+        let created = history.first().map(|event| event.at.to_utc().to_rfc3339());
+        let bytes = contents
+            .bytes()
+            .chain(created.unwrap_or_default().into_bytes());
+        for byte in bytes {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+
+        let base = ID_ALPHABET.len() as u64;
+        (0..ID_LENGTH)
+            .map(|_| {
+                let c = ID_ALPHABET[(hash % base) as usize] as char;
+                hash /= base;
+                c
+            })
+            .collect()
+    }
+
+    /// Whether some text is shaped like an item ID.
+    pub fn is_id(s: &str) -> bool {
+        s.len() == ID_LENGTH && s.bytes().all(|b| ID_ALPHABET.contains(&b))
+    }
+
     pub fn mark_completed(&mut self) {
-        let event = ("completed".to_string(), Local::now());
-        self.history.push(event);
+        self.history.push(ItemEvent::now("completed"));
     }
 
     pub fn mark_deleted(&mut self) {
-        let event = ("deleted".to_string(), Local::now());
+        self.history.push(ItemEvent::now("deleted"));
+    }
+
+    pub fn mark_snoozed(&mut self, until: DateTime<Local>) {
+        self.snoozed_until = Some(until);
+        self.history.push(ItemEvent::now("snoozed"));
+    }
+
+    /// Replace the item's contents and notes. Its tags are found again in the
+    /// new contents, and the "edited" event keeps the previous contents if
+    /// they changed.
+    pub fn mark_edited(&mut self, contents: String, notes: String) {
+        let mut event = ItemEvent::now("edited");
+        if contents != self.contents {
+            self.tags = tags_in(&contents);
+            event.detail = Some(std::mem::replace(&mut self.contents, contents));
+        }
+        self.notes = notes;
         self.history.push(event);
     }
 
     pub fn mark_restored(&mut self) {
-        let event = ("restored".to_string(), Local::now());
-        self.history.push(event);
+        self.history.push(ItemEvent::now("restored"));
     }
-}
 
-/// A stack of items.
-// TODO: Is there a better stack type than Vec? We only ever perform one command
-// per CLI invocation, so there isn't a huge need for stack optimization yet.
-pub type Stack = Vec<Item>;
+    /// The next item in a recurring item's series, due the first time the
+    /// recurrence comes around after `now`. That's counted from the item's due
+    /// time, or from `now` if it had none. The notes carry over. `None` if the
+    /// item doesn't recur.
+    pub fn next_occurrence(&self, now: DateTime<Local>) -> Option<Item> {
+        let recurrence = self.recurrence.as_ref()?;
+        let mut due = self.due.unwrap_or(now);
+        loop {
+            due = recurrence.after(due)?;
+            if due > now {
+                break;
+            }
+        }
+
+        let mut next = Item::new(&self.contents);
+        next.notes = self.notes.clone();
+        next.due = Some(due);
+        next.recurrence = Some(recurrence.clone());
+        next.series = Some(self.series.clone().unwrap_or_else(|| self.id.clone()));
+        Some(next)
+    }
+}
 
-pub enum Backend {
-    HomeDir,
+/// How often a recurring item comes back.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(into = "String", try_from = "String")]
+pub enum Recurrence {
+    /// Every N days, like "daily", "weekly" or "every 3 days".
+    Days(u32),
+    /// On these days of the week, like "mon,wed,fri".
+    Weekdays(Vec<Weekday>),
 }
 
-impl Backend {
-    pub fn load(&self, stack_name: &str) -> Result<Stack, impl Error> {
+impl Recurrence {
+    /// The first time after `time` that the recurrence comes around, at the
+    /// same time of day.
+    pub fn after(&self, time: DateTime<Local>) -> Option<DateTime<Local>> {
         match self {
-            Backend::HomeDir => load_from_homedir(stack_name),
+            Recurrence::Days(n) => time.checked_add_days(Days::new((*n).into())),
+            Recurrence::Weekdays(weekdays) => (1..=7)
+                .filter_map(|n| time.checked_add_days(Days::new(n)))
+                .find(|next| weekdays.contains(&next.weekday())),
         }
     }
+}
 
-    pub fn save(&self, stack_name: &str, items: Stack) -> Result<(), impl Error> {
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Backend::HomeDir => save_to_homedir(stack_name, items),
+            Recurrence::Days(1) => write!(f, "daily"),
+            Recurrence::Days(7) => write!(f, "weekly"),
+            Recurrence::Days(n) => write!(f, "every {} days", n),
+            Recurrence::Weekdays(weekdays) => {
+                let names: Vec<String> = weekdays
+                    .iter()
+                    .map(|weekday| weekday.to_string().to_lowercase())
+                    .collect();
+                write!(f, "{}", names.join(","))
+            }
         }
     }
+}
 
-    pub fn list_stacks(&self) -> Result<Vec<String>, impl Error> {
-        match self {
-            Backend::HomeDir => list_stacks_from_homedir(),
-        }
+impl FromStr for Recurrence {
+    type Err = String;
+
+    /// Parse "daily", "weekly", "every N days" (or "every N day"), or a list
+    /// of days of the week like "mon,wed,fri".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let recurrence = match words[..] {
+            ["daily"] => Some(Recurrence::Days(1)),
+            ["weekly"] => Some(Recurrence::Days(7)),
+            ["every", n, "day" | "days"] => n.parse().ok().filter(|n| *n > 0).map(Recurrence::Days),
+            _ => s
+                .split(',')
+                .map(|day| day.trim().parse::<Weekday>().ok())
+                .collect::<Option<Vec<Weekday>>>()
+                .map(Recurrence::Weekdays),
+        };
+        recurrence.ok_or_else(|| {
+            format!(
+                "expected daily, weekly, \"every N days\", or days like mon,wed,fri, but got {:?}",
+                s
+            )
+        })
     }
 }
 
-/// Save a stack of items.
-// TODO: Create a custom error. This is returning raw filesystem errors.
-fn save_to_homedir(stack_name: &str, items: Stack) -> Result<(), impl Error> {
-    let data_path: String = sigi_file(stack_name);
-    let json: String = serde_json::to_string(&items).unwrap();
-    let result = fs::write(&data_path, &json);
-    if result.is_err() && result.as_ref().unwrap_err().kind() == ErrorKind::NotFound {
-        fs::create_dir_all(sigi_path()).unwrap();
-        fs::write(data_path, json)
-    } else {
-        result
+impl From<Recurrence> for String {
+    fn from(recurrence: Recurrence) -> Self {
+        recurrence.to_string()
     }
 }
 
-/// Load a stack of items.
-// TODO: Create a custom error. This is returning raw serialization errors.
-fn load_from_homedir(stack_name: &str) -> Result<Stack, impl Error> {
-    let data_path: String = sigi_file(stack_name);
-    let read_result = fs::read_to_string(data_path);
-    if read_result.is_err() && read_result.as_ref().unwrap_err().kind() == ErrorKind::NotFound {
-        return Ok(vec![]);
+impl TryFrom<String> for Recurrence {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
     }
+}
+
+/// Tag names, without their leading `+` or `#`.
+pub type Tags = Vec<String>;
+
+/// The tags written in some text: words starting with `+` or `#`, like
+/// `+review` or `#oncall`. Tags are lowercase, and each appears once.
+pub fn tags_in(text: &str) -> Tags {
+    let mut tags: Tags = vec![];
+    text.split_whitespace()
+        .filter(|word| word.starts_with(['+', '#']))
+        .filter_map(|word| parse_tag(word.trim_end_matches(['.', ',', ';', ':', '!', '?', ')'])))
+        .for_each(|tag| {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        });
+    tags
+}
+
+/// Parse a single tag, with or without its leading `+` or `#`. Tags start
+/// with a letter, and contain only letters, numbers, `-`, `_` and `/`. (So
+/// `+1` and `#42` aren't tags.)
+pub fn parse_tag(s: &str) -> Option<String> {
+    let name = s.strip_prefix(['+', '#']).unwrap_or(s);
+    let starts_with_letter = name.chars().next().is_some_and(char::is_alphabetic);
+    let valid = name
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '/'));
+    if starts_with_letter && valid {
+        Some(name.to_lowercase())
+    } else {
+        None
+    }
+}
+
+/// A stack of items.
+// TODO: Is there a better stack type than Vec? We only ever perform one command
+// per CLI invocation, so there isn't a huge need for stack optimization yet.
+pub type Stack = Vec<Item>;
+
+/// Where stacks are persisted.
+///
+/// Implementors only need `load`, `save`, and `list_stacks`. The remaining
+/// operations have default implementations in terms of those three, and can
+/// be overridden by stores that support them natively.
+pub trait Backend {
+    /// Load all items of a stack, oldest first. A stack that doesn't exist yet is empty.
+    fn load(&self, stack_name: &str) -> Result<Stack, SigiError>;
 
-    let json = read_result.unwrap();
-    let result = serde_json::from_str(&json);
+    /// Replace all items of a stack.
+    fn save(&self, stack_name: &str, items: Stack) -> Result<(), SigiError>;
 
-    if result.is_err() {
-        let v1result = v1_load(&json);
-        if let Ok(v1stack) = v1result {
-            return Ok(v1_to_modern(v1stack));
+    /// List the names of all known stacks, in no particular order.
+    fn list_stacks(&self) -> Result<Vec<String>, SigiError>;
+
+    /// Add an item to the top of a stack.
+    fn push(&self, stack_name: &str, item: Item) -> Result<(), SigiError> {
+        self.append(stack_name, vec![item])
+    }
+
+    /// Remove and return the top item of a stack, if there is one.
+    fn pop(&self, stack_name: &str) -> Result<Option<Item>, SigiError> {
+        let mut items = self.load(stack_name)?;
+        let item = items.pop();
+        if item.is_some() {
+            self.save(stack_name, items)?;
         }
+        Ok(item)
     }
 
-    result
-}
+    /// Add several items to the top of a stack, keeping their order. The last
+    /// item ends up on top.
+    fn append(&self, stack_name: &str, items: Stack) -> Result<(), SigiError> {
+        let mut all_items = self.load(stack_name)?;
+        all_items.extend(items);
+        self.save(stack_name, all_items)
+    }
+
+    /// Load a piece of metadata saved with [`Backend::save_meta`], like the
+    /// undo journal.
+    ///
+    /// By default there's no metadata, so there's nothing to undo.
+    fn load_meta(&self, _key: &str) -> Result<Option<String>, SigiError> {
+        Ok(None)
+    }
 
-fn list_stacks_from_homedir() -> Result<Vec<String>, impl Error> {
-    let dot_json = ".json";
-    fs::read_dir(sigi_path()).map(|files| {
-        files
-            .map(|file| file.unwrap().file_name().into_string().unwrap())
-            .filter(|filename| filename.ends_with(dot_json))
-            .map(|filename| filename.strip_suffix(dot_json).unwrap().to_string())
-            .collect::<Vec<_>>()
-    })
+    /// Save a piece of metadata about the store, separately from its stacks.
+    ///
+    /// By default metadata is discarded.
+    fn save_meta(&self, _key: &str, _value: &str) -> Result<(), SigiError> {
+        Ok(())
+    }
+
+    /// Take exclusive access to the store until the returned [`Lock`] is
+    /// dropped. Each effect holds this for its whole load/modify/save cycle.
+    ///
+    /// By default there's no locking at all.
+    fn lock(&self) -> Result<Lock, SigiError> {
+        Ok(Lock::none())
+    }
 }
 
-fn v1_sigi_path() -> PathBuf {
-    let home = env::var("HOME").or_else(|_| env::var("HOMEDRIVE")).unwrap();
-    let path = format!("{}/.local/share/sigi", home);
-    PathBuf::from(&path)
+/// How long to wait for another process to release a [`Lock`].
+pub const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Exclusive access to a store. (See [`Backend::lock`]) Released when dropped.
+pub struct Lock {
+    _file: Option<File>,
+    release: Option<Box<dyn FnOnce()>>,
 }
 
-fn sigi_path() -> String {
-    let sigi_base = ProjectDirs::from("org", "sigi-cli", "sigi").unwrap();
-    let sigi_path = sigi_base.data_dir();
-    let v1_path = v1_sigi_path();
+impl Lock {
+    /// A lock that doesn't exclude anything.
+    pub fn none() -> Self {
+        Lock {
+            _file: None,
+            release: None,
+        }
+    }
 
-    if v1_path.exists() && !sigi_path.exists() {
-        fs::rename(v1_path, sigi_path).unwrap();
+    /// A lock already taken somewhere else, like on a server, which `release`
+    /// gives back.
+    pub fn held(release: impl FnOnce() + 'static) -> Self {
+        Lock {
+            _file: None,
+            release: Some(Box::new(release)),
+        }
     }
 
-    sigi_path.to_string_lossy().to_string()
+    /// An advisory lock on a file, created if needed. Waits up to
+    /// [`LOCK_TIMEOUT`] for other processes to release it.
+    pub fn file(path: &Path) -> Result<Self, SigiError> {
+        let file = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)?;
+        let start = Instant::now();
+        loop {
+            match file.try_lock() {
+                Ok(()) => {
+                    return Ok(Lock {
+                        _file: Some(file),
+                        release: None,
+                    })
+                }
+                Err(TryLockError::Error(err)) => return Err(err.into()),
+                Err(TryLockError::WouldBlock) if start.elapsed() < LOCK_TIMEOUT => {
+                    thread::sleep(Duration::from_millis(5));
+                }
+                Err(TryLockError::WouldBlock) => return Err(SigiError::LockTimeout),
+            }
+        }
+    }
 }
 
-fn sigi_file(filename: &str) -> String {
-    let path = format!("{}/{}.json", sigi_path(), filename);
-    PathBuf::from(&path).to_string_lossy().to_string()
+impl Drop for Lock {
+    fn drop(&mut self) {
+        if let Some(release) = self.release.take() {
+            release();
+        }
+    }
 }
 
-/// A single stack item. Used for backwards compatibility with versions of Sigi v1.
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct V1Item {
-    name: String,
-    created: DateTime<Local>,
-    succeeded: Option<DateTime<Local>>,
-    failed: Option<DateTime<Local>>,
+/// The name of a directory holding project-local stacks. (See [`find_project_dir`])
+pub const PROJECT_DIR_NAME: &str = ".sigi";
+
+/// Find the closest project-local data directory, looking in `start` and then
+/// each of its parents, the same way git finds a `.git` directory.
+pub fn find_project_dir(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_DIR_NAME))
+        .find(|dir| dir.is_dir())
 }
 
-/// A stack of items. Used for backwards compatibility with versions of Sigi v1.
-type V1Stack = Vec<V1Item>;
+/// The user's default data directory, e.g. `~/.local/share/sigi` on Linux.
+///
+/// Data from sigi v1 (which always used `~/.local/share/sigi`) is moved here
+/// the first time it's needed.
+pub fn default_data_dir() -> Result<PathBuf, SigiError> {
+    let sigi_base = ProjectDirs::from("org", "sigi-cli", "sigi")
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No home directory found"))?;
+    let sigi_path = sigi_base.data_dir();
+
+    if let Some(v1_path) = v1_sigi_path() {
+        if v1_path.exists() && !sigi_path.exists() {
+            fs::rename(v1_path, sigi_path)?;
+        }
+    }
 
-/// Attempt to read a V1 format file.
-fn v1_load(json_blob: &str) -> Result<V1Stack, impl Error> {
-    serde_json::from_str(json_blob)
+    Ok(sigi_path.to_path_buf())
 }
 
-fn v1_to_modern(v1stack: V1Stack) -> Stack {
-    v1stack
-        .into_iter()
-        .map(|v1item| {
-            // Translate the old keys to entries.
-            let mut history: ItemHistory = vec![
-                Some(("created", v1item.created)),
-                v1item.succeeded.map(|dt| ("completed", dt)),
-                v1item.failed.map(|dt| ("deleted", dt)),
-            ]
-            .into_iter()
-            .flatten()
-            .map(|(s, dt)| (s.to_string(), dt))
-            .collect();
-            history.sort_by_key(|(_, dt)| *dt);
-            Item {
-                contents: v1item.name,
-                history,
-            }
-        })
-        .collect()
+fn v1_sigi_path() -> Option<PathBuf> {
+    let home = env::var("HOME").or_else(|_| env::var("HOMEDRIVE")).ok()?;
+    let path = format!("{}/.local/share/sigi", home);
+    Some(PathBuf::from(&path))
 }
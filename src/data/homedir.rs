@@ -0,0 +1,186 @@
+use super::{Backend, Item, ItemEvent, ItemHistory, Lock, Stack};
+use crate::error::SigiError;
+use std::fs::{self, File};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+const LOCK_FILE: &str = "sigi.lock";
+
+/// Stacks saved as JSON files, one per stack, in a data directory.
+///
+/// Files are replaced atomically, and [`Backend::lock`] holds a lock file in
+/// the directory so concurrent sigi processes take turns.
+pub struct HomeDir {
+    path: PathBuf,
+}
+
+impl HomeDir {
+    /// Use the user's default data directory. (See [`super::default_data_dir`])
+    pub fn new() -> Result<Self, SigiError> {
+        Ok(HomeDir::at(&super::default_data_dir()?))
+    }
+
+    /// Use a specific directory. It will be created when a stack is first saved.
+    pub fn at(path: &Path) -> Self {
+        HomeDir {
+            path: path.to_path_buf(),
+        }
+    }
+}
+
+impl Backend for HomeDir {
+    fn load(&self, stack_name: &str) -> Result<Stack, SigiError> {
+        load_from_homedir(&self.path, stack_name)
+    }
+
+    fn save(&self, stack_name: &str, items: Stack) -> Result<(), SigiError> {
+        save_to_homedir(&self.path, stack_name, items)
+    }
+
+    fn list_stacks(&self) -> Result<Vec<String>, SigiError> {
+        list_stacks_from_homedir(&self.path)
+    }
+
+    fn load_meta(&self, key: &str) -> Result<Option<String>, SigiError> {
+        match fs::read_to_string(meta_file(&self.path, key)) {
+            Ok(value) => Ok(Some(value)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn save_meta(&self, key: &str, value: &str) -> Result<(), SigiError> {
+        fs::create_dir_all(&self.path)?;
+        Ok(write_atomically(&meta_file(&self.path, key), value)?)
+    }
+
+    fn lock(&self) -> Result<Lock, SigiError> {
+        fs::create_dir_all(&self.path)?;
+        Lock::file(&self.path.join(LOCK_FILE))
+    }
+}
+
+/// Save a stack of items.
+fn save_to_homedir(sigi_path: &Path, stack_name: &str, items: Stack) -> Result<(), SigiError> {
+    let data_path = sigi_file(sigi_path, stack_name);
+    let json: String =
+        serde_json::to_string(&items).map_err(|err| SigiError::corrupt_data(stack_name, err))?;
+    let result = write_atomically(&data_path, &json);
+    if result.is_err() && result.as_ref().unwrap_err().kind() == ErrorKind::NotFound {
+        fs::create_dir_all(sigi_path)?;
+        write_atomically(&data_path, &json)?;
+        Ok(())
+    } else {
+        Ok(result?)
+    }
+}
+
+/// Write to a temporary file first, then move it into place. A crash part way
+/// through leaves the previous version of the file untouched.
+fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let tmp_path = path.with_file_name(format!(".{}.{}.tmp", file_name, process::id()));
+
+    let mut tmp = File::create(&tmp_path)?;
+    let written = tmp
+        .write_all(contents.as_bytes())
+        .and_then(|_| tmp.sync_all())
+        .and_then(|_| fs::rename(&tmp_path, path));
+
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    written
+}
+
+/// Load a stack of items.
+fn load_from_homedir(sigi_path: &Path, stack_name: &str) -> Result<Stack, SigiError> {
+    let data_path = sigi_file(sigi_path, stack_name);
+    let json = match fs::read_to_string(data_path) {
+        Ok(json) => json,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err.into()),
+    };
+
+    let result = serde_json::from_str(&json);
+
+    if result.is_err() {
+        let v1result = v1_load(&json);
+        if let Ok(v1stack) = v1result {
+            return Ok(v1_to_modern(v1stack));
+        }
+    }
+
+    result.map_err(|err| SigiError::corrupt_data(stack_name, err))
+}
+
+fn list_stacks_from_homedir(sigi_path: &Path) -> Result<Vec<String>, SigiError> {
+    let dot_json = ".json";
+    let files = match fs::read_dir(sigi_path) {
+        Ok(files) => files,
+        // Nothing has been saved here yet.
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err.into()),
+    };
+    let mut stacks = vec![];
+    for file in files {
+        // Skip anything without a UTF-8 name; sigi never creates those.
+        if let Ok(filename) = file?.file_name().into_string() {
+            if let Some(stack) = filename.strip_suffix(dot_json) {
+                stacks.push(stack.to_string());
+            }
+        }
+    }
+    Ok(stacks)
+}
+
+fn sigi_file(sigi_path: &Path, stack_name: &str) -> PathBuf {
+    sigi_path.join(format!("{}.json", stack_name))
+}
+
+/// Metadata lives beside the stacks, without a ".json" extension so it's
+/// never mistaken for a stack.
+fn meta_file(sigi_path: &Path, key: &str) -> PathBuf {
+    sigi_path.join(format!("sigi.{}", key))
+}
+
+/// A single stack item. Used for backwards compatibility with versions of Sigi v1.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct V1Item {
+    name: String,
+    created: DateTime<Local>,
+    succeeded: Option<DateTime<Local>>,
+    failed: Option<DateTime<Local>>,
+}
+
+/// A stack of items. Used for backwards compatibility with versions of Sigi v1.
+type V1Stack = Vec<V1Item>;
+
+/// Attempt to read a V1 format file.
+fn v1_load(json_blob: &str) -> Result<V1Stack, serde_json::Error> {
+    serde_json::from_str(json_blob)
+}
+
+fn v1_to_modern(v1stack: V1Stack) -> Stack {
+    v1stack
+        .into_iter()
+        .map(|v1item| {
+            // Translate the old keys to entries.
+            let mut history: ItemHistory = vec![
+                Some(("created", v1item.created)),
+                v1item.succeeded.map(|dt| ("completed", dt)),
+                v1item.failed.map(|dt| ("deleted", dt)),
+            ]
+            .into_iter()
+            .flatten()
+            .map(|(name, dt)| ItemEvent::new(name, dt))
+            .collect();
+            history.sort_by_key(|event| event.at);
+            Item::from_history(v1item.name, history)
+        })
+        .collect()
+}
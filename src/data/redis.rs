@@ -0,0 +1,162 @@
+use super::{Backend, Item, Lock, Stack, LOCK_TIMEOUT};
+use crate::error::SigiError;
+use redis::{Client, Commands, Connection, ExistenceCheck, SetExpiry, SetOptions};
+use std::cell::RefCell;
+use std::hash::{BuildHasher, RandomState};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use std::{process, thread};
+
+/// The set of all stack names.
+const STACKS_KEY: &str = "sigi:stacks";
+
+/// Held by whoever is changing the store. (See [`Backend::lock`])
+const LOCK_KEY: &str = "sigi:lock";
+
+/// Deletes the lock only if it still holds our token, so a client whose lock
+/// expired can't release someone else's.
+const UNLOCK_SCRIPT: &str =
+    "if redis.call('get', KEYS[1]) == ARGV[1] then return redis.call('del', KEYS[1]) else return 0 end";
+
+/// Stacks saved as Redis lists, so several machines can share them through one server.
+///
+/// Each stack is a list at `sigi:stack:<NAME>` holding JSON-serialized items,
+/// oldest first. Every effect, pushes and pops included, takes the `sigi:lock`
+/// key before touching the store, so concurrent users of the same stack don't
+/// overwrite each other's changes. The lock expires after [`LOCK_TIMEOUT`] in
+/// case its holder dies.
+pub struct Redis {
+    conn: Rc<RefCell<Connection>>,
+}
+
+impl Redis {
+    /// Connect to a server, e.g. `redis://127.0.0.1:6379/`.
+    pub fn open(url: &str) -> Result<Self, SigiError> {
+        let conn = Client::open(url)?.get_connection()?;
+        Ok(Redis {
+            conn: Rc::new(RefCell::new(conn)),
+        })
+    }
+}
+
+fn stack_key(stack_name: &str) -> String {
+    format!("sigi:stack:{}", stack_name)
+}
+
+fn meta_key(key: &str) -> String {
+    format!("sigi:meta:{}", key)
+}
+
+/// A value no other client will pick for its lock.
+fn lock_token() -> String {
+    format!(
+        "{}-{:x}",
+        process::id(),
+        RandomState::new().hash_one(Instant::now())
+    )
+}
+
+fn to_json(stack_name: &str, items: &[Item]) -> Result<Vec<String>, SigiError> {
+    items
+        .iter()
+        .map(|item| {
+            serde_json::to_string(item).map_err(|err| SigiError::corrupt_data(stack_name, err))
+        })
+        .collect()
+}
+
+fn from_json(stack_name: &str, blob: &str) -> Result<Item, SigiError> {
+    serde_json::from_str(blob).map_err(|err| SigiError::corrupt_data(stack_name, err))
+}
+
+impl Backend for Redis {
+    fn load(&self, stack_name: &str) -> Result<Stack, SigiError> {
+        let blobs: Vec<String> = self
+            .conn
+            .borrow_mut()
+            .lrange(stack_key(stack_name), 0, -1)?;
+        let items = blobs
+            .iter()
+            .map(|blob| from_json(stack_name, blob))
+            .collect::<Result<_, _>>()?;
+        Ok(items)
+    }
+
+    fn save(&self, stack_name: &str, items: Stack) -> Result<(), SigiError> {
+        let key = stack_key(stack_name);
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .del(&key)
+            .ignore()
+            .sadd(STACKS_KEY, stack_name)
+            .ignore();
+        if !items.is_empty() {
+            pipe.rpush(&key, to_json(stack_name, &items)?).ignore();
+        }
+        pipe.query::<()>(&mut self.conn.borrow_mut())?;
+        Ok(())
+    }
+
+    fn list_stacks(&self) -> Result<Vec<String>, SigiError> {
+        Ok(self.conn.borrow_mut().smembers(STACKS_KEY)?)
+    }
+
+    fn pop(&self, stack_name: &str) -> Result<Option<Item>, SigiError> {
+        let blob: Option<String> = self.conn.borrow_mut().rpop(stack_key(stack_name), None)?;
+        match blob {
+            Some(blob) => Ok(Some(from_json(stack_name, &blob)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn append(&self, stack_name: &str, items: Stack) -> Result<(), SigiError> {
+        let mut pipe = redis::pipe();
+        pipe.atomic().sadd(STACKS_KEY, stack_name).ignore();
+        if !items.is_empty() {
+            pipe.rpush(stack_key(stack_name), to_json(stack_name, &items)?)
+                .ignore();
+        }
+        pipe.query::<()>(&mut self.conn.borrow_mut())?;
+        Ok(())
+    }
+
+    fn load_meta(&self, key: &str) -> Result<Option<String>, SigiError> {
+        Ok(self.conn.borrow_mut().get(meta_key(key))?)
+    }
+
+    fn save_meta(&self, key: &str, value: &str) -> Result<(), SigiError> {
+        Ok(self.conn.borrow_mut().set(meta_key(key), value)?)
+    }
+
+    fn lock(&self) -> Result<Lock, SigiError> {
+        let token = lock_token();
+        let options = SetOptions::default()
+            .conditional_set(ExistenceCheck::NX)
+            .with_expiration(SetExpiry::PX(LOCK_TIMEOUT.as_millis() as u64));
+        let start = Instant::now();
+        loop {
+            let taken: Option<String> = self
+                .conn
+                .borrow_mut()
+                .set_options(LOCK_KEY, &token, options)?;
+            if taken.is_some() {
+                break;
+            }
+            if start.elapsed() >= LOCK_TIMEOUT {
+                return Err(SigiError::LockTimeout);
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        let conn = Rc::clone(&self.conn);
+        Ok(Lock::held(move || {
+            // If this fails, the lock still expires on its own.
+            let _: Result<i64, _> = redis::cmd("EVAL")
+                .arg(UNLOCK_SCRIPT)
+                .arg(1)
+                .arg(LOCK_KEY)
+                .arg(&token)
+                .query(&mut conn.borrow_mut());
+        }))
+    }
+}
@@ -0,0 +1,357 @@
+use super::{Backend, HomeDir, Item, ItemEvent, Lock, Recurrence, Stack};
+use crate::effects::META_KEYS;
+use crate::error::SigiError;
+use chrono::{DateTime, Local};
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const DATABASE_FILE: &str = "sigi.sqlite";
+
+/// Schema changes, applied in order. `PRAGMA user_version` records how many
+/// of these have run, so only add to the end of this list.
+const MIGRATIONS: &[&str] = &[
+    "
+    CREATE TABLE stacks (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE
+    );
+    CREATE TABLE items (
+        id INTEGER PRIMARY KEY,
+        stack_id INTEGER NOT NULL REFERENCES stacks(id) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        contents TEXT NOT NULL
+    );
+    CREATE INDEX items_by_stack ON items(stack_id, position);
+    CREATE TABLE item_history (
+        item_id INTEGER NOT NULL REFERENCES items(id) ON DELETE CASCADE,
+        seq INTEGER NOT NULL,
+        event TEXT NOT NULL,
+        at TEXT NOT NULL
+    );
+    CREATE INDEX item_history_by_item ON item_history(item_id, seq);
+",
+    "
+    CREATE TABLE meta (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+",
+    "
+    ALTER TABLE items ADD COLUMN uid TEXT;
+",
+    "
+    ALTER TABLE items ADD COLUMN tags TEXT;
+",
+    "
+    ALTER TABLE items ADD COLUMN due TEXT;
+",
+    "
+    ALTER TABLE items ADD COLUMN snoozed_until TEXT;
+",
+    "
+    ALTER TABLE items ADD COLUMN recurrence TEXT;
+    ALTER TABLE items ADD COLUMN series TEXT;
+",
+    "
+    ALTER TABLE items ADD COLUMN notes TEXT NOT NULL DEFAULT '';
+",
+    "
+    ALTER TABLE item_history ADD COLUMN detail TEXT;
+",
+];
+
+/// Stacks saved as tables in a SQLite database in a data directory.
+///
+/// Unlike [`HomeDir`], pushing and popping only touch the affected rows, so
+/// large stacks stay fast. The first time the database is created, any
+/// existing JSON stacks are copied into it. The JSON files are left in place.
+pub struct Sqlite {
+    conn: Connection,
+    lock_path: PathBuf,
+}
+
+impl Sqlite {
+    /// Open (or create) the database in a data directory. A new database
+    /// imports any JSON stacks already in that directory.
+    pub fn open(dir: &Path) -> Result<Self, SigiError> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(DATABASE_FILE);
+        // Don't let another process see the database before it's set up.
+        let _lock = Lock::file(&lock_path_for(&path))?;
+        let is_new = !path.exists();
+        let sqlite = Sqlite::open_at(&path)?;
+        if is_new {
+            if let Err(err) = sqlite.import(&HomeDir::at(dir)) {
+                // Try again from scratch next time instead of leaving a half-imported database.
+                drop(sqlite);
+                fs::remove_file(&path)?;
+                return Err(err);
+            }
+        }
+        Ok(sqlite)
+    }
+
+    /// Open (or create) the database at a specific path, without importing
+    /// anything. Its lock file is kept beside it.
+    pub fn open_at(path: &Path) -> Result<Self, SigiError> {
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+
+        let version: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            let tx = conn.unchecked_transaction()?;
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", i + 1)?;
+            tx.commit()?;
+        }
+
+        Ok(Sqlite {
+            conn,
+            lock_path: lock_path_for(path),
+        })
+    }
+
+    /// Copy every stack from another store into this database, along with its
+    /// metadata, like retention policies and the undo journal.
+    pub fn import(&self, other: &dyn Backend) -> Result<(), SigiError> {
+        for stack in other.list_stacks()? {
+            self.save(&stack, other.load(&stack)?)?;
+        }
+        for key in META_KEYS {
+            if let Some(value) = other.load_meta(key)? {
+                self.save_meta(key, &value)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn stack_id(&self, stack_name: &str) -> Result<i64, rusqlite::Error> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO stacks (name) VALUES (?1)",
+            params![stack_name],
+        )?;
+        self.conn.query_row(
+            "SELECT id FROM stacks WHERE name = ?1",
+            params![stack_name],
+            |row| row.get(0),
+        )
+    }
+
+    fn insert_item(
+        &self,
+        stack_id: i64,
+        position: i64,
+        item: &Item,
+    ) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "INSERT INTO items (stack_id, position, contents, uid, tags, due,
+                                snoozed_until, recurrence, series, notes)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                stack_id,
+                position,
+                item.contents,
+                item.id,
+                item.tags.join(" "),
+                item.due,
+                item.snoozed_until,
+                item.recurrence
+                    .as_ref()
+                    .map(|recurrence| recurrence.to_string()),
+                item.series,
+                item.notes
+            ],
+        )?;
+        let item_id = self.conn.last_insert_rowid();
+        let mut insert_event = self.conn.prepare_cached(
+            "INSERT INTO item_history (item_id, seq, event, at, detail)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        for (seq, event) in item.history.iter().enumerate() {
+            insert_event.execute(params![item_id, seq, event.name, event.at, event.detail])?;
+        }
+        Ok(())
+    }
+
+    /// Load an item's history. Items saved before IDs or tags existed (`uid`
+    /// or `tags` is NULL) get them here.
+    fn load_item(&self, row: ItemRow) -> Result<Item, rusqlite::Error> {
+        let mut select_events = self.conn.prepare_cached(
+            "SELECT event, at, detail FROM item_history WHERE item_id = ?1 ORDER BY seq",
+        )?;
+        let history = select_events
+            .query_map(params![row.item_id], |row| {
+                Ok(ItemEvent {
+                    name: row.get(0)?,
+                    at: row.get(1)?,
+                    detail: row.get(2)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+        let mut item = Item::from_history(row.contents, history);
+        if let Some(uid) = row.uid {
+            item.id = uid;
+        }
+        if let Some(tags) = row.tags {
+            item.tags = tags.split_whitespace().map(str::to_string).collect();
+        }
+        item.due = row.due;
+        item.snoozed_until = row.snoozed_until;
+        item.recurrence = row.recurrence;
+        item.series = row.series;
+        item.notes = row.notes;
+        Ok(item)
+    }
+}
+
+/// The columns of the items table, as selected by [`ITEM_COLUMNS`].
+struct ItemRow {
+    item_id: i64,
+    contents: String,
+    uid: Option<String>,
+    tags: Option<String>,
+    due: Option<DateTime<Local>>,
+    snoozed_until: Option<DateTime<Local>>,
+    recurrence: Option<Recurrence>,
+    series: Option<String>,
+    notes: String,
+}
+
+const ITEM_COLUMNS: &str = "items.id, items.contents, items.uid, items.tags, items.due, \
+    items.snoozed_until, items.recurrence, items.series, items.notes";
+
+impl ItemRow {
+    fn from_row(row: &rusqlite::Row) -> Result<Self, rusqlite::Error> {
+        Ok(ItemRow {
+            item_id: row.get(0)?,
+            contents: row.get(1)?,
+            uid: row.get(2)?,
+            tags: row.get(3)?,
+            due: row.get(4)?,
+            snoozed_until: row.get(5)?,
+            recurrence: row
+                .get::<_, Option<String>>(6)?
+                .map(|s| s.parse())
+                .transpose()
+                .map_err(|err: String| {
+                    rusqlite::Error::FromSqlConversionFailure(6, Type::Text, err.into())
+                })?,
+            series: row.get(7)?,
+            notes: row.get(8)?,
+        })
+    }
+}
+
+fn lock_path_for(database_path: &Path) -> PathBuf {
+    let mut lock_path = database_path.as_os_str().to_owned();
+    lock_path.push(".lock");
+    PathBuf::from(lock_path)
+}
+
+impl Backend for Sqlite {
+    fn load(&self, stack_name: &str) -> Result<Stack, SigiError> {
+        let mut select_items = self.conn.prepare_cached(&format!(
+            "SELECT {} FROM items
+             JOIN stacks ON stacks.id = items.stack_id
+             WHERE stacks.name = ?1
+             ORDER BY items.position",
+            ITEM_COLUMNS
+        ))?;
+        let rows = select_items
+            .query_map(params![stack_name], ItemRow::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        let items = rows
+            .into_iter()
+            .map(|row| self.load_item(row))
+            .collect::<Result<_, _>>()?;
+        Ok(items)
+    }
+
+    fn save(&self, stack_name: &str, items: Stack) -> Result<(), SigiError> {
+        let tx = self.conn.unchecked_transaction()?;
+        let stack_id = self.stack_id(stack_name)?;
+        tx.execute("DELETE FROM items WHERE stack_id = ?1", params![stack_id])?;
+        for (position, item) in items.iter().enumerate() {
+            self.insert_item(stack_id, position as i64, item)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn list_stacks(&self) -> Result<Vec<String>, SigiError> {
+        let mut select_stacks = self.conn.prepare_cached("SELECT name FROM stacks")?;
+        let stacks = select_stacks
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        Ok(stacks)
+    }
+
+    fn pop(&self, stack_name: &str) -> Result<Option<Item>, SigiError> {
+        let tx = self.conn.unchecked_transaction()?;
+        let top = tx
+            .query_row(
+                &format!(
+                    "SELECT {} FROM items
+                     JOIN stacks ON stacks.id = items.stack_id
+                     WHERE stacks.name = ?1
+                     ORDER BY items.position DESC LIMIT 1",
+                    ITEM_COLUMNS
+                ),
+                params![stack_name],
+                ItemRow::from_row,
+            )
+            .optional()?;
+        let item = match top {
+            Some(row) => {
+                let item_id = row.item_id;
+                let item = self.load_item(row)?;
+                tx.execute("DELETE FROM items WHERE id = ?1", params![item_id])?;
+                Some(item)
+            }
+            None => None,
+        };
+        tx.commit()?;
+        Ok(item)
+    }
+
+    fn append(&self, stack_name: &str, items: Stack) -> Result<(), SigiError> {
+        let tx = self.conn.unchecked_transaction()?;
+        let stack_id = self.stack_id(stack_name)?;
+        let next_position: i64 = tx.query_row(
+            "SELECT COALESCE(MAX(position) + 1, 0) FROM items WHERE stack_id = ?1",
+            params![stack_id],
+            |row| row.get(0),
+        )?;
+        for (offset, item) in items.iter().enumerate() {
+            self.insert_item(stack_id, next_position + offset as i64, item)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn load_meta(&self, key: &str) -> Result<Option<String>, SigiError> {
+        let value = self
+            .conn
+            .query_row(
+                "SELECT value FROM meta WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(value)
+    }
+
+    fn save_meta(&self, key: &str, value: &str) -> Result<(), SigiError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+            params![key, value],
+        )?;
+        Ok(())
+    }
+
+    fn lock(&self) -> Result<Lock, SigiError> {
+        Lock::file(&self.lock_path)
+    }
+}
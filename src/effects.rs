@@ -1,467 +1,1860 @@
-use crate::data::{Backend, Item};
-use crate::output::OutputFormat;
+use crate::data::{Backend, Item, Stack, Tags};
+use crate::error::SigiError;
+use crate::output::{NoiseLevel, OutputFormat};
+use chrono::{DateTime, Local};
+use regex::Regex;
 
-const HISTORY_SUFFIX: &str = "_history";
+mod journal;
+pub use journal::JournalEntry;
+use journal::Recorder;
 
-// TODO: Consider more shuffle words: https://docs.factorcode.org/content/article-shuffle-words.html
+mod retention;
+pub use retention::Retention;
 
+pub(crate) const HISTORY_SUFFIX: &str = "_history";
+
+/// Every piece of metadata effects keep with [`Backend::save_meta`], so a
+/// whole store can be copied.
+pub const META_KEYS: [&str; 2] = [journal::JOURNAL_KEY, retention::RETENTION_KEY];
+
+/// Something to do to a stack. Run it against a [`Backend`] to get an [`EffectResult`].
 pub enum StackEffect {
-    Push { stack: String, content: String },
-    Complete { stack: String },
-    Delete { stack: String },
-    DeleteAll { stack: String },
-    Pick { stack: String, indices: Vec<usize> },
-    Move { stack: String, dest: String },
-    MoveAll { stack: String, dest: String },
-    Swap { stack: String },
-    Rot { stack: String },
-    Next { stack: String },
-    Peek { stack: String },
-    ListAll { stack: String },
+    Push {
+        stack: String,
+        item: Item,
+    },
+    Complete {
+        stack: String,
+    },
+    Delete {
+        stack: String,
+    },
+    DeleteAll {
+        stack: String,
+    },
+    Pick {
+        stack: String,
+        items: Vec<ItemRef>,
+    },
+    Move {
+        stack: String,
+        dest: String,
+    },
+    MoveAll {
+        stack: String,
+        dest: String,
+    },
+    Swap {
+        stack: String,
+    },
+    /// Send the top item down to third: ( x y z -- z x y ). With only two
+    /// items, swap them.
+    Rot {
+        stack: String,
+    },
+    Next {
+        stack: String,
+    },
+    // Shuffle words, after Factor's. Stack effects are written with the top of
+    // the stack on the right: https://docs.factorcode.org/content/article-shuffle-words.html
+    /// Copy the top item as a new item: ( x -- x x )
+    Dup {
+        stack: String,
+    },
+    /// Copy the second item to the top as a new item: ( x y -- x y x )
+    Over {
+        stack: String,
+    },
+    /// Delete the second item: ( x y -- y )
+    Nip {
+        stack: String,
+    },
+    /// Copy the top item below the second as a new item: ( x y -- y x y )
+    Tuck {
+        stack: String,
+    },
+    /// Send the top item down to third: ( x y z -- z x y ), like Factor's
+    /// -rot. Sigi's [`StackEffect::Rot`] has always done the same. With only
+    /// two items, swap them.
+    MinusRot {
+        stack: String,
+    },
+    /// Bring the item at position `n` up to the top, like Forth's roll.
+    /// Like the other shuffle words, this skips snoozed items.
+    Roll {
+        stack: String,
+        n: usize,
+    },
+    /// Send the top item down to position `n`; the inverse of roll.
+    Unroll {
+        stack: String,
+        n: usize,
+    },
+    Snooze {
+        stack: String,
+        at: DateTime<Local>,
+    },
+    Peek {
+        stack: String,
+        view: View,
+    },
+    Show {
+        stack: String,
+        item: ItemRef,
+    },
+    Edit {
+        stack: String,
+        item: ItemRef,
+        contents: String,
+        notes: String,
+    },
+    Amend {
+        stack: String,
+        item: ItemRef,
+        amendment: Amendment,
+    },
+    ListAll {
+        stack: String,
+        view: View,
+    },
     ListStacks,
-    Head { stack: String, n: usize },
-    Tail { stack: String, n: usize },
-    Count { stack: String },
-    IsEmpty { stack: String },
+    Head {
+        stack: String,
+        n: usize,
+        view: View,
+    },
+    Tail {
+        stack: String,
+        n: usize,
+        view: View,
+    },
+    Count {
+        stack: String,
+        tags: Tags,
+    },
+    IsEmpty {
+        stack: String,
+    },
+    Due {
+        overdue: bool,
+    },
+    Find {
+        stack: String,
+        search: Search,
+    },
+    Restore {
+        stack: String,
+        query: RestoreQuery,
+    },
+    History {
+        stack: String,
+        filter: EventFilter,
+    },
+    Prune {
+        stack: String,
+        dry_run: bool,
+    },
+    ShowRetention {
+        stack: String,
+    },
+    SetRetention {
+        stack: String,
+        policy: Retention,
+    },
+    Undo {
+        n: usize,
+    },
+    Redo {
+        n: usize,
+    },
 }
 
-impl StackEffect {
-    pub fn run(self, backend: &Backend, output: &OutputFormat) {
-        use StackEffect::*;
-        match self {
-            Push { stack, content } => push_content(stack, content, backend, output),
-            Complete { stack } => complete_latest_item(stack, backend, output),
-            Delete { stack } => delete_latest_item(stack, backend, output),
-            DeleteAll { stack } => delete_all_items(stack, backend, output),
-            Pick { stack, indices } => pick_indices(stack, indices, backend, output),
-            Move { stack, dest } => move_latest_item(stack, dest, backend, output),
-            MoveAll { stack, dest } => move_all_items(stack, dest, backend, output),
-            Swap { stack } => swap_latest_two_items(stack, backend, output),
-            Rot { stack } => rotate_latest_three_items(stack, backend, output),
-            Next { stack } => next_to_latest(stack, backend, output),
-            Peek { stack } => peek_latest_item(stack, backend, output),
-            ListAll { stack } => list_all_items(stack, backend, output),
-            ListStacks => list_stacks(backend, output),
-            Head { stack, n } => list_n_latest_items(stack, n, backend, output),
-            Tail { stack, n } => list_n_oldest_items(stack, n, backend, output),
-            Count { stack } => count_all_items(stack, backend, output),
-            IsEmpty { stack } => is_empty(stack, backend, output),
-        }
+/// Which of a stack's items to show.
+#[derive(Debug, Clone, Default)]
+pub struct View {
+    /// Only items with all of these tags.
+    pub tags: Tags,
+    /// Show snoozed items too.
+    pub snoozed: bool,
+}
+
+impl View {
+    fn shows(&self, item: &Item, now: DateTime<Local>) -> bool {
+        item.has_tags(&self.tags) && (self.snoozed || !item.is_snoozed(now))
     }
 }
 
-fn push_content(stack: String, content: String, backend: &Backend, output: &OutputFormat) {
-    let item = Item::new(&content);
-    push_item(stack, item, backend, output);
+/// What to search for, and which stacks to search.
+#[derive(Debug, Clone)]
+pub struct Search {
+    /// Matched against each item's contents.
+    pub pattern: Regex,
+    /// Search every stack, not just the current one.
+    pub all_stacks: bool,
+    /// Search history stacks too.
+    pub history: bool,
 }
 
-fn push_item(stack: String, item: Item, backend: &Backend, output: &OutputFormat) {
-    let contents = item.contents.clone();
+/// An item in a stack, by its position (most recent first) or its ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ItemRef {
+    Position(usize),
+    Id(String),
+    /// The first item that isn't snoozed, the one `peek` shows.
+    Current,
+}
 
-    let items = if let Ok(items) = backend.load(&stack) {
-        let mut items = items;
-        items.push(item);
-        items
-    } else {
-        vec![item]
-    };
+impl ItemRef {
+    /// The item's position in a stack, if it's there.
+    fn position_in(&self, items: &[Item]) -> Option<usize> {
+        match self {
+            ItemRef::Position(i) if *i < items.len() => Some(*i),
+            ItemRef::Position(_) => None,
+            ItemRef::Id(id) => items.iter().rev().position(|item| &item.id == id),
+            ItemRef::Current => current_indices(items).first().map(|&i| items.len() - 1 - i),
+        }
+    }
+}
 
-    backend.save(&stack, items).unwrap();
+impl std::str::FromStr for ItemRef {
+    type Err = String;
 
-    output.log(vec!["action", "item"], vec![vec!["Created", &contents]]);
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(i) = s.parse() {
+            Ok(ItemRef::Position(i))
+        } else if Item::is_id(s) {
+            Ok(ItemRef::Id(s.to_string()))
+        } else {
+            Err(format!(
+                "expected a position or an item ID, but got {:?}",
+                s
+            ))
+        }
+    }
 }
 
-fn complete_latest_item(stack: String, backend: &Backend, output: &OutputFormat) {
-    if let Ok(items) = backend.load(&stack) {
-        let mut items = items;
-        if let Some(item) = items.pop() {
-            let mut item = item;
-            item.mark_completed();
-
-            // Push the now-marked-completed item to history stack.
-            push_item(
-                stack_history_of(&stack),
-                item.clone(),
-                backend,
-                &OutputFormat::Silent,
-            );
+/// A change to an item's contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Amendment {
+    /// New contents.
+    Replace(String),
+    /// Replace the first `old` with `new`, or every one if `all` is set. Like
+    /// sed's `s/old/new/` and `s/old/new/g`.
+    Substitute { old: String, new: String, all: bool },
+}
 
-            // Save the original stack without that item.
-            backend.save(&stack, items).unwrap();
+impl Amendment {
+    /// Text like `s/old/new/` or `s/old/new/g` is a substitution, and anything
+    /// else is new contents.
+    pub fn parse(text: &str) -> Self {
+        let parts: Vec<&str> = match text.strip_prefix("s/") {
+            Some(rest) => rest.split('/').collect(),
+            None => vec![],
+        };
+        match parts[..] {
+            [old, new, flags @ ("" | "g")] if !old.is_empty() => Amendment::Substitute {
+                old: old.to_string(),
+                new: new.to_string(),
+                all: flags == "g",
+            },
+            _ => Amendment::Replace(text.to_string()),
+        }
+    }
 
-            output.log(
-                vec!["action", "item"],
-                vec![vec!["Completed", &item.contents]],
-            );
+    fn apply(&self, contents: &str) -> String {
+        match self {
+            Amendment::Replace(text) => text.clone(),
+            Amendment::Substitute {
+                old,
+                new,
+                all: true,
+            } => contents.replace(old, new),
+            Amendment::Substitute {
+                old,
+                new,
+                all: false,
+            } => contents.replacen(old, new, 1),
         }
     }
+}
 
-    if output.is_nonquiet_for_humans() {
-        peek_latest_item(stack, backend, output);
+/// Which events to show from a stack's history. Empty filters match everything.
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    /// Event names, like "completed" or "deleted".
+    pub events: Vec<String>,
+    /// Only events at or after this time.
+    pub since: Option<DateTime<Local>>,
+    /// Only events at or before this time.
+    pub until: Option<DateTime<Local>>,
+    /// Only items containing this text (ignoring case).
+    pub search: Option<String>,
+    /// Only items in the same recurring series as the item with this ID.
+    pub series: Option<String>,
+}
+
+impl EventFilter {
+    fn matches(&self, item: &Item, event: &str, at: &DateTime<Local>) -> bool {
+        (self.events.is_empty() || self.events.iter().any(|e| e == event))
+            && self.since.is_none_or(|since| *at >= since)
+            && self.until.is_none_or(|until| *at <= until)
+            && self
+                .search
+                .as_ref()
+                .is_none_or(|text| item.contents.to_lowercase().contains(&text.to_lowercase()))
+            && self
+                .series
+                .as_ref()
+                .is_none_or(|series| item.series.as_ref() == Some(series) || item.id == *series)
     }
 }
 
-fn delete_latest_item(stack: String, backend: &Backend, output: &OutputFormat) {
-    if let Ok(items) = backend.load(&stack) {
-        let mut items = items;
-        if let Some(item) = items.pop() {
-            let mut item = item;
-            item.mark_deleted();
+/// One event in an item's history.
+#[derive(Debug, Clone)]
+pub struct HistoryEvent {
+    pub event: String,
+    pub at: DateTime<Local>,
+    /// More about the event, like an edited item's previous contents.
+    pub detail: Option<String>,
+    /// The item's contents.
+    pub item: String,
+    /// The item's ID.
+    pub id: String,
+    /// The ID of the item's recurring series, if it has one.
+    pub series: Option<String>,
+}
 
-            // Push the now-marked-deleted item to history stack.
-            push_item(
-                stack_history_of(&stack),
-                item.clone(),
-                backend,
-                &OutputFormat::Silent,
-            );
+/// An item, and where it is.
+#[derive(Debug, Clone)]
+pub struct StackItem {
+    pub stack: String,
+    pub position: usize,
+    pub item: Item,
+}
 
-            // Save the original stack without that item.
-            backend.save(&stack, items).unwrap();
+/// Which items in a stack's history to restore. Items matching either are restored.
+#[derive(Debug, Clone, Default)]
+pub struct RestoreQuery {
+    /// Items in the history stack.
+    pub items: Vec<ItemRef>,
+    /// Text to search for in each item (ignoring case).
+    pub search: Option<String>,
+}
 
-            output.log(
-                vec!["action", "item"],
-                vec![vec!["Deleted", &item.contents]],
-            );
+impl StackEffect {
+    /// Apply the effect to the backend's stacks. Nothing is printed; see
+    /// [`EffectResult::log`] for that.
+    pub fn run(self, backend: &dyn Backend) -> Result<EffectResult, SigiError> {
+        match self {
+            StackEffect::Undo { n } => {
+                let _lock = backend.lock()?;
+                journal::undo(backend, n).map(EffectResult::Undone)
+            }
+            StackEffect::Redo { n } => {
+                let _lock = backend.lock()?;
+                journal::redo(backend, n).map(EffectResult::Redone)
+            }
+            effect => {
+                let mut results = StackEffect::run_together(vec![effect], backend)?;
+                Ok(results.remove(0))
+            }
         }
     }
 
-    if output.is_nonquiet_for_humans() {
-        peek_latest_item(stack, backend, output);
+    /// Run several effects as one, e.g. picking items and then completing
+    /// them: under one lock, and undone together as a single journal entry
+    /// named after the last effect. Undo and redo can't be run this way.
+    pub fn run_together(
+        effects: Vec<StackEffect>,
+        backend: &dyn Backend,
+    ) -> Result<Vec<EffectResult>, SigiError> {
+        let Some(last) = effects.last() else {
+            return Ok(vec![]);
+        };
+        let (name, stack) = (last.name(), last.stack().unwrap_or_default().to_string());
+
+        // Hold the lock throughout, so concurrent runs can't interleave their
+        // loads and saves, and record every change so they can be undone.
+        let _lock = backend.lock()?;
+        let recorder = Recorder::new(backend);
+        let results = effects
+            .into_iter()
+            .map(|effect| effect.run_recorded(backend, &recorder))
+            .collect::<Result<_, _>>()?;
+        recorder.finish(name, &stack)?;
+        Ok(results)
     }
-}
 
-fn delete_all_items(stack: String, backend: &Backend, output: &OutputFormat) {
-    if let Ok(items) = backend.load(&stack) {
-        let mut items = items;
-        items.iter_mut().for_each(|item| item.mark_deleted());
-        let n_deleted = items.len();
+    /// Run the effect with its changes going through `recorder`, for
+    /// [`StackEffect::run`] and [`StackEffect::run_together`] to journal.
+    fn run_recorded(
+        self,
+        backend: &dyn Backend,
+        recorder: &Recorder,
+    ) -> Result<EffectResult, SigiError> {
+        use StackEffect::*;
+        let stack = self.stack().unwrap_or_default().to_string();
 
-        // Push the now-marked-deleted items to history stack.
-        let history_stack = &stack_history_of(&stack);
-        let mut history = backend.load(history_stack).unwrap_or_default();
-        history.append(&mut items);
-        backend.save(history_stack, history).unwrap();
+        let archives = matches!(
+            self,
+            Complete { .. } | Delete { .. } | DeleteAll { .. } | Nip { .. }
+        );
 
-        // Save the original stack as empty now.
-        backend.save(&stack, vec![]).unwrap();
+        // Items whose snooze is over go back on top before anything looks at
+        // the top of the stack. This isn't undoable; it would only happen again.
+        if matches!(
+            self,
+            Complete { .. }
+                | Delete { .. }
+                | Pick { .. }
+                | Move { .. }
+                | Swap { .. }
+                | Rot { .. }
+                | Next { .. }
+                | Dup { .. }
+                | Over { .. }
+                | Nip { .. }
+                | Tuck { .. }
+                | MinusRot { .. }
+                | Roll { .. }
+                | Unroll { .. }
+                | Snooze { .. }
+                | Peek { .. }
+                | Show { .. }
+                | Edit { .. }
+                | Amend { .. }
+                | ListAll { .. }
+                | Head { .. }
+                | Tail { .. }
+        ) {
+            wake_snoozed_items(&stack, backend)?;
+        }
 
-        output.log(
-            vec!["action", "item"],
-            vec![vec!["Deleted", &format!("{} items", n_deleted)]],
-        );
+        let backend = recorder;
+        let result = match self {
+            Push { stack, item } => push_item(stack, item, backend),
+            Complete { stack } => complete_latest_item(stack, backend),
+            Delete { stack } => delete_latest_item(stack, backend),
+            DeleteAll { stack } => delete_all_items(stack, backend),
+            Pick { stack, items } => pick_items(stack, items, backend),
+            Move { stack, dest } => move_latest_item(stack, dest, backend),
+            MoveAll { stack, dest } => move_all_items(stack, dest, backend),
+            Swap { stack } => swap_latest_two_items(stack, backend),
+            Rot { stack } => rotate_latest_three_items(stack, backend),
+            Next { stack } => next_to_latest(stack, backend),
+            Dup { stack } => copy_item(stack, 0, 0, backend),
+            Over { stack } => copy_item(stack, 1, 0, backend),
+            Nip { stack } => delete_second_item(stack, backend),
+            Tuck { stack } => copy_item(stack, 0, 2, backend),
+            MinusRot { stack } => rotate_latest_three_items(stack, backend),
+            Roll { stack, n } => roll_item(stack, n, backend),
+            Unroll { stack, n } => unroll_item(stack, n, backend),
+            Snooze { stack, at } => snooze_latest_item(stack, at, backend),
+            Peek { stack, view } => peek_latest_item(stack, view, backend),
+            Show { stack, item } => show_item(stack, item, backend),
+            Edit {
+                stack,
+                item,
+                contents,
+                notes,
+            } => edit_item(stack, item, contents, notes, backend),
+            Amend {
+                stack,
+                item,
+                amendment,
+            } => amend_item(stack, item, amendment, backend),
+            ListAll { stack, view } => list_all_items(stack, view, backend),
+            ListStacks => list_stacks(backend),
+            Head { stack, n, view } => list_n_latest_items(stack, n, view, backend),
+            Tail { stack, n, view } => list_n_oldest_items(stack, n, view, backend),
+            Count { stack, tags } => count_all_items(stack, tags, backend),
+            Due { overdue } => list_due_items(overdue, backend),
+            Find { stack, search } => find_items(stack, search, backend),
+            IsEmpty { stack } => is_empty(stack, backend),
+            Restore { stack, query } => restore_items(stack, query, backend),
+            History { stack, filter } => list_history(stack, filter, backend),
+            Prune { stack, dry_run } => prune_history(stack, dry_run, backend),
+            ShowRetention { stack } => show_retention(stack, backend),
+            SetRetention { stack, policy } => set_retention(stack, policy, backend),
+            Undo { .. } | Redo { .. } => unreachable!("[BUG] Undo and redo aren't recorded."),
+        }?;
+        if archives {
+            auto_prune(&stack, backend)?;
+        }
+        Ok(result)
     }
-}
 
-fn pick_indices(stack: String, indices: Vec<usize>, backend: &Backend, output: &OutputFormat) {
-    if let Ok(items) = backend.load(&stack) {
-        let mut items = items;
-        let mut seen: Vec<usize> = vec![];
-        seen.reserve_exact(indices.len());
-        let indices: Vec<usize> = indices.iter().map(|i| items.len() - 1 - i).rev().collect();
-        for i in indices {
-            if i > items.len() || seen.contains(&i) {
-                // TODO: What should be the output here? Some stderr?
-                // command.log("Pick", "ignoring out-of-bounds index");
-                // command.log("Pick", "ignoring duplicate index");
-                continue;
-            }
-            let i = i - seen.iter().filter(|j| j < &&i).count();
-            let picked = items.remove(i);
-            items.push(picked);
-            seen.push(i);
+    /// A short name for the effect, e.g. "delete-all".
+    pub fn name(&self) -> &'static str {
+        use StackEffect::*;
+        match self {
+            Push { .. } => "push",
+            Complete { .. } => "complete",
+            Delete { .. } => "delete",
+            DeleteAll { .. } => "delete-all",
+            Pick { .. } => "pick",
+            Move { .. } => "move",
+            MoveAll { .. } => "move-all",
+            Swap { .. } => "swap",
+            Rot { .. } => "rot",
+            Next { .. } => "next",
+            Dup { .. } => "dup",
+            Over { .. } => "over",
+            Nip { .. } => "nip",
+            Tuck { .. } => "tuck",
+            MinusRot { .. } => "-rot",
+            Roll { .. } => "roll",
+            Unroll { .. } => "unroll",
+            Snooze { .. } => "snooze",
+            Peek { .. } => "peek",
+            Show { .. } => "show",
+            Edit { .. } => "edit",
+            Amend { .. } => "amend",
+            ListAll { .. } => "list",
+            ListStacks => "list-stacks",
+            Head { .. } => "head",
+            Tail { .. } => "tail",
+            Count { .. } => "count",
+            Due { overdue: false } => "due",
+            Due { overdue: true } => "overdue",
+            Find { .. } => "find",
+            IsEmpty { .. } => "is-empty",
+            Restore { .. } => "restore",
+            History { .. } => "history",
+            Prune { .. } => "prune",
+            ShowRetention { .. } | SetRetention { .. } => "retention",
+            Undo { .. } => "undo",
+            Redo { .. } => "redo",
         }
+    }
 
-        backend.save(&stack, items).unwrap();
-
-        if output.is_nonquiet_for_humans() {
-            list_n_latest_items(stack, seen.len(), backend, output);
+    /// The stack the effect acts on, if it acts on one.
+    pub fn stack(&self) -> Option<&str> {
+        use StackEffect::*;
+        match self {
+            Push { stack, .. }
+            | Complete { stack }
+            | Delete { stack }
+            | DeleteAll { stack }
+            | Pick { stack, .. }
+            | Move { stack, .. }
+            | MoveAll { stack, .. }
+            | Swap { stack }
+            | Rot { stack }
+            | Next { stack }
+            | Dup { stack }
+            | Over { stack }
+            | Nip { stack }
+            | Tuck { stack }
+            | MinusRot { stack }
+            | Roll { stack, .. }
+            | Unroll { stack, .. }
+            | Snooze { stack, .. }
+            | Peek { stack, .. }
+            | Show { stack, .. }
+            | Edit { stack, .. }
+            | Amend { stack, .. }
+            | ListAll { stack, .. }
+            | Head { stack, .. }
+            | Tail { stack, .. }
+            | Count { stack, .. }
+            | IsEmpty { stack }
+            | Find { stack, .. }
+            | Restore { stack, .. }
+            | History { stack, .. }
+            | Prune { stack, .. }
+            | ShowRetention { stack }
+            | SetRetention { stack, .. } => Some(stack),
+            ListStacks | Due { .. } | Undo { .. } | Redo { .. } => None,
         }
     }
 }
 
-fn move_latest_item(source: String, dest: String, backend: &Backend, output: &OutputFormat) {
-    if let Ok(items) = backend.load(&source) {
-        let mut items = items;
-        if let Some(item) = items.pop() {
-            backend.save(&source, items).unwrap();
+/// What running a [`StackEffect`] did. Items are listed most recent first.
+#[derive(Debug, Clone)]
+pub enum EffectResult {
+    /// An item was pushed.
+    Pushed { stack: String, item: Item },
+    /// The latest item (if any) was completed and moved to the history stack.
+    /// If it was a recurring item, `next` is the next one in its series.
+    Completed {
+        stack: String,
+        item: Option<Item>,
+        next: Option<Item>,
+        now: Option<Item>,
+    },
+    /// The latest item (if any) was deleted and moved to the history stack.
+    Deleted {
+        stack: String,
+        item: Option<Item>,
+        now: Option<Item>,
+    },
+    /// Every item was deleted and moved to the history stack.
+    DeletedAll { stack: String, count: usize },
+    /// Items were moved to the top of the stack. `top` holds the moved
+    /// items, and is empty if nothing changed.
+    Reordered { stack: String, top: Vec<Item> },
+    /// The latest item (if any) was sent to the bottom of the stack.
+    Cycled { stack: String, now: Option<Item> },
+    /// The latest item (if any) was hidden until later, and sent to the
+    /// bottom of the stack until then.
+    Snoozed {
+        stack: String,
+        item: Option<Item>,
+        now: Option<Item>,
+    },
+    /// The latest item (if any) was moved to another stack.
+    Moved {
+        stack: String,
+        dest: String,
+        item: Option<Item>,
+    },
+    /// Every item was moved to another stack.
+    MovedAll {
+        stack: String,
+        dest: String,
+        count: usize,
+    },
+    /// The latest item (in the requested view), if any.
+    Peeked { stack: String, now: Option<Item> },
+    /// An item with its position, if it was found.
+    Shown {
+        stack: String,
+        item: Option<(usize, Item)>,
+    },
+    /// An item's new contents and notes, if it was found and changed.
+    Edited { stack: String, item: Option<Item> },
+    /// Some of the stack's items, with their positions.
+    Listed {
+        stack: String,
+        items: Vec<(usize, Item)>,
+    },
+    /// The names of all stacks, sorted.
+    Stacks { stacks: Vec<String> },
+    /// The number of items in the stack.
+    Counted { stack: String, count: usize },
+    /// Whether the stack has no items.
+    IsEmpty { stack: String, empty: bool },
+    /// Items with due dates across all stacks, soonest first. If `overdue`,
+    /// only those past their due dates.
+    Due {
+        items: Vec<StackItem>,
+        overdue: bool,
+    },
+    /// Items matching a search, by stack name and then position.
+    Found { items: Vec<StackItem> },
+    /// Items were moved from the history stack back onto the stack, and
+    /// marked as restored.
+    Restored { stack: String, items: Vec<Item> },
+    /// Events from the items of a stack and its history stack, most recent first.
+    History {
+        stack: String,
+        events: Vec<HistoryEvent>,
+    },
+    /// Items were removed from the history stack for good, most recent
+    /// first. On a dry run, they were only found.
+    Pruned {
+        stack: String,
+        items: Vec<Item>,
+        dry_run: bool,
+    },
+    /// The retention policy of a stack's history.
+    RetentionPolicy { stack: String, policy: Retention },
+    /// Effects that were undone, most recent first.
+    Undone(Vec<JournalEntry>),
+    /// Effects that were undone before, and were applied again.
+    Redone(Vec<JournalEntry>),
+}
 
-            output.log(
-                vec!["action", "new-stack", "old-stack"],
-                vec![vec!["Move", &dest, &source]],
-            );
+fn push_item(stack: String, item: Item, backend: &dyn Backend) -> Result<EffectResult, SigiError> {
+    backend.push(&stack, item.clone())?;
+    Ok(EffectResult::Pushed { stack, item })
+}
 
-            push_item(dest, item, backend, &OutputFormat::Silent);
+fn complete_latest_item(stack: String, backend: &dyn Backend) -> Result<EffectResult, SigiError> {
+    let mut item = take_current_item(&stack, backend)?.map(|mut item| {
+        item.mark_completed();
+        item
+    });
+
+    let mut next = None;
+    if let Some(item) = &mut item {
+        // A recurring item comes back as a new item in the same series.
+        next = item.next_occurrence(Local::now());
+        if let Some(next) = &next {
+            item.series = next.series.clone();
         }
+
+        // Push the now-marked-completed item to history stack.
+        backend.push(&stack_history_of(&stack), item.clone())?;
     }
+    if let Some(next) = &next {
+        backend.push(&stack, next.clone())?;
+    }
+
+    let now = current_item(&backend.load(&stack)?);
+    Ok(EffectResult::Completed {
+        stack,
+        item,
+        next,
+        now,
+    })
 }
 
-fn move_all_items(source: String, dest: String, backend: &Backend, output: &OutputFormat) {
-    if let Ok(src_items) = backend.load(&source) {
-        let count = src_items.len();
+fn delete_latest_item(stack: String, backend: &dyn Backend) -> Result<EffectResult, SigiError> {
+    let item = take_current_item(&stack, backend)?.map(|mut item| {
+        item.mark_deleted();
+        item
+    });
 
-        if !src_items.is_empty() {
-            let all_items = match backend.load(&dest) {
-                Ok(dest_items) => {
-                    let mut all_items = dest_items;
-                    for item in src_items {
-                        all_items.push(item);
-                    }
-                    all_items
-                }
-                _ => src_items,
-            };
+    if let Some(item) = &item {
+        // Push the now-marked-deleted item to history stack.
+        backend.push(&stack_history_of(&stack), item.clone())?;
+    }
 
-            backend.save(&dest, all_items).unwrap();
-            backend.save(&source, vec![]).unwrap();
-        }
+    let now = current_item(&backend.load(&stack)?);
+    Ok(EffectResult::Deleted { stack, item, now })
+}
 
-        output.log(
-            vec!["action", "new-stack", "old-stack", "num-moved"],
-            vec![vec!["Move All", &dest, &source, &count.to_string()]],
-        );
+fn delete_all_items(stack: String, backend: &dyn Backend) -> Result<EffectResult, SigiError> {
+    let mut items = backend.load(&stack)?;
+    items.iter_mut().for_each(|item| item.mark_deleted());
+    let count = items.len();
+
+    // Push the now-marked-deleted items to history stack.
+    backend.append(&stack_history_of(&stack), items)?;
+
+    // Save the original stack as empty now.
+    backend.save(&stack, vec![])?;
+
+    Ok(EffectResult::DeletedAll { stack, count })
+}
+
+fn pick_items(
+    stack: String,
+    refs: Vec<ItemRef>,
+    backend: &dyn Backend,
+) -> Result<EffectResult, SigiError> {
+    let mut items = backend.load(&stack)?;
+    let mut seen: Vec<usize> = vec![];
+    seen.reserve_exact(refs.len());
+    let indices: Vec<usize> = refs
+        .iter()
+        // Out-of-bounds indices and unknown IDs are ignored.
+        .filter_map(|item_ref| item_ref.position_in(&items))
+        .map(|i| items.len() - 1 - i)
+        .rev()
+        .collect();
+    for i in indices {
+        if seen.contains(&i) {
+            // Duplicate indices are ignored.
+            continue;
+        }
+        let i = i - seen.iter().filter(|j| j < &&i).count();
+        let picked = items.remove(i);
+        items.push(picked);
+        seen.push(i);
     }
+
+    let top = latest_n(&items, seen.len());
+    backend.save(&stack, items)?;
+
+    Ok(EffectResult::Reordered { stack, top })
 }
 
-fn swap_latest_two_items(stack: String, backend: &Backend, output: &OutputFormat) {
-    if let Ok(items) = backend.load(&stack) {
-        let mut items = items;
+fn move_latest_item(
+    stack: String,
+    dest: String,
+    backend: &dyn Backend,
+) -> Result<EffectResult, SigiError> {
+    require_stack(&stack, backend)?;
+
+    let item = take_current_item(&stack, backend)?;
+    if let Some(item) = &item {
+        backend.push(&dest, item.clone())?;
+    }
 
-        if items.len() < 2 {
-            return;
-        }
+    Ok(EffectResult::Moved { stack, dest, item })
+}
 
-        let a = items.pop().unwrap();
-        let b = items.pop().unwrap();
-        items.push(a);
-        items.push(b);
+fn move_all_items(
+    stack: String,
+    dest: String,
+    backend: &dyn Backend,
+) -> Result<EffectResult, SigiError> {
+    require_stack(&stack, backend)?;
 
-        backend.save(&stack, items).unwrap();
+    let src_items = backend.load(&stack)?;
+    let count = src_items.len();
 
-        if output.is_nonquiet_for_humans() {
-            list_n_latest_items(stack, 2, backend, output);
-        }
+    if !src_items.is_empty() {
+        backend.append(&dest, src_items)?;
+        backend.save(&stack, vec![])?;
     }
+
+    Ok(EffectResult::MovedAll { stack, dest, count })
 }
 
-fn rotate_latest_three_items(stack: String, backend: &Backend, output: &OutputFormat) {
-    if let Ok(items) = backend.load(&stack) {
-        let mut items = items;
+fn restore_items(
+    stack: String,
+    query: RestoreQuery,
+    backend: &dyn Backend,
+) -> Result<EffectResult, SigiError> {
+    let RestoreQuery { items, search } = query;
+    let history_stack = stack_history_of(&stack);
+    let history = backend.load(&history_stack)?;
+
+    // With nothing chosen, show what could be restored.
+    if items.is_empty() && search.is_none() {
+        return Ok(EffectResult::Listed {
+            stack: history_stack,
+            items: history.into_iter().rev().enumerate().collect(),
+        });
+    }
 
-        if items.len() < 3 {
-            swap_latest_two_items(stack, backend, output);
-            return;
-        }
+    let search = search.map(|text| text.to_lowercase());
+    let indices: Vec<usize> = items
+        .iter()
+        .filter_map(|item_ref| item_ref.position_in(&history))
+        .collect();
+    let n_history = history.len();
+    let (mut restored, kept): (Vec<_>, Vec<_>) =
+        history.into_iter().enumerate().partition(|(i, item)| {
+            let position = n_history - 1 - i;
+            let matches_search = search
+                .as_ref()
+                .is_some_and(|text| item.contents.to_lowercase().contains(text));
+            indices.contains(&position) || matches_search
+        });
+
+    if restored.is_empty() {
+        let items = vec![];
+        return Ok(EffectResult::Restored { stack, items });
+    }
 
-        let a = items.pop().unwrap();
-        let b = items.pop().unwrap();
-        let c = items.pop().unwrap();
+    let kept = kept.into_iter().map(|(_, item)| item).collect();
+    backend.save(&history_stack, kept)?;
 
-        items.push(a);
-        items.push(c);
-        items.push(b);
+    restored
+        .iter_mut()
+        .for_each(|(_, item)| item.mark_restored());
+    let restored: Stack = restored.into_iter().map(|(_, item)| item).collect();
+    backend.append(&stack, restored.clone())?;
 
-        backend.save(&stack, items).unwrap();
+    let items = restored.into_iter().rev().collect();
+    Ok(EffectResult::Restored { stack, items })
+}
 
-        if output.is_nonquiet_for_humans() {
-            list_n_latest_items(stack, 3, backend, output);
+fn list_history(
+    stack: String,
+    mut filter: EventFilter,
+    backend: &dyn Backend,
+) -> Result<EffectResult, SigiError> {
+    let mut items = backend.load(&stack)?;
+    items.extend(backend.load(&stack_history_of(&stack))?);
+
+    // Any item in a series stands for the whole series.
+    if let Some(id) = &filter.series {
+        if let Some(series) = items
+            .iter()
+            .find(|item| item.id == *id)
+            .and_then(|item| item.series.clone())
+        {
+            filter.series = Some(series);
         }
     }
+
+    let mut events: Vec<HistoryEvent> = items
+        .iter()
+        .flat_map(|item| {
+            item.history
+                .iter()
+                .filter(|event| filter.matches(item, &event.name, &event.at))
+                .map(|event| HistoryEvent {
+                    event: event.name.clone(),
+                    at: event.at,
+                    detail: event.detail.clone(),
+                    item: item.contents.clone(),
+                    id: item.id.clone(),
+                    series: item.series.clone(),
+                })
+        })
+        .collect();
+    events.sort_by_key(|event| std::cmp::Reverse(event.at));
+
+    Ok(EffectResult::History { stack, events })
 }
 
-fn next_to_latest(stack: String, backend: &Backend, output: &OutputFormat) {
-    if let Ok(items) = backend.load(&stack) {
-        let mut items = items;
-        if items.is_empty() {
-            return;
-        }
-        let to_the_back = items.pop().unwrap();
-        items.insert(0, to_the_back);
+fn prune_history(
+    stack: String,
+    dry_run: bool,
+    backend: &dyn Backend,
+) -> Result<EffectResult, SigiError> {
+    let policy = retention::policy(backend, &stack)?;
+    let pruned = prune_with(&stack, &policy, dry_run, backend)?;
+    let items = pruned.into_iter().rev().collect();
+    Ok(EffectResult::Pruned {
+        stack,
+        items,
+        dry_run,
+    })
+}
 
-        backend.save(&stack, items).unwrap();
+fn auto_prune(stack: &str, backend: &dyn Backend) -> Result<(), SigiError> {
+    let policy = retention::policy(backend, stack)?;
+    if policy.auto_prune {
+        prune_with(stack, &policy, false, backend)?;
+    }
+    Ok(())
+}
 
-        if output.is_nonquiet_for_humans() {
-            peek_latest_item(stack, backend, output);
-        }
+/// Remove items from a stack's history as the policy says, and return them.
+fn prune_with(
+    stack: &str,
+    policy: &Retention,
+    dry_run: bool,
+    backend: &dyn Backend,
+) -> Result<Stack, SigiError> {
+    let history_stack = stack_history_of(stack);
+    let history = backend.load(&history_stack)?;
+    let (kept, pruned) = retention::split(history, policy, Local::now());
+    if !dry_run && !pruned.is_empty() {
+        backend.save(&history_stack, kept)?;
     }
+    Ok(pruned)
 }
 
-fn peek_latest_item(stack: String, backend: &Backend, output: &OutputFormat) {
-    if let OutputFormat::Silent = output {
-        return;
+fn show_retention(stack: String, backend: &dyn Backend) -> Result<EffectResult, SigiError> {
+    let policy = retention::policy(backend, &stack)?;
+    Ok(EffectResult::RetentionPolicy { stack, policy })
+}
+
+fn set_retention(
+    stack: String,
+    policy: Retention,
+    backend: &dyn Backend,
+) -> Result<EffectResult, SigiError> {
+    retention::set_policy(backend, &stack, policy.clone())?;
+    Ok(EffectResult::RetentionPolicy { stack, policy })
+}
+
+fn swap_latest_two_items(stack: String, backend: &dyn Backend) -> Result<EffectResult, SigiError> {
+    shuffle_current_items(stack, 2, backend, |items| items.swap(0, 1))
+}
+
+fn rotate_latest_three_items(
+    stack: String,
+    backend: &dyn Backend,
+) -> Result<EffectResult, SigiError> {
+    // With only two items, rotating them is swapping them.
+    let n = current_indices(&backend.load(&stack)?).len().min(3);
+    shuffle_current_items(stack, n, backend, |items| items.rotate_left(1))
+}
+
+fn roll_item(stack: String, n: usize, backend: &dyn Backend) -> Result<EffectResult, SigiError> {
+    shuffle_current_items(stack, n + 1, backend, |items| items.rotate_right(1))
+}
+
+fn unroll_item(stack: String, n: usize, backend: &dyn Backend) -> Result<EffectResult, SigiError> {
+    shuffle_current_items(stack, n + 1, backend, |items| items.rotate_left(1))
+}
+
+/// Rearrange the `n` most-current items, given to `shuffle` most recent
+/// first. Snoozed items are skipped and stay where they are. Nothing happens
+/// unless there are at least two items, and `n` of them.
+fn shuffle_current_items(
+    stack: String,
+    n: usize,
+    backend: &dyn Backend,
+    shuffle: impl FnOnce(&mut [Item]),
+) -> Result<EffectResult, SigiError> {
+    let mut items = backend.load(&stack)?;
+    let slots: Vec<usize> = current_indices(&items).into_iter().take(n).collect();
+    if n < 2 || slots.len() < n {
+        let top = vec![];
+        return Ok(EffectResult::Reordered { stack, top });
     }
 
-    if let Ok(items) = backend.load(&stack) {
-        let top_item = items.last().map(|i| i.contents.as_str());
+    let mut top: Vec<Item> = slots.iter().map(|&i| items[i].clone()).collect();
+    shuffle(&mut top);
+    for (&i, item) in slots.iter().zip(&top) {
+        items[i] = item.clone();
+    }
+    backend.save(&stack, items)?;
 
-        let output_it = |it| output.log_always(vec!["position", "item"], it);
+    Ok(EffectResult::Reordered { stack, top })
+}
 
-        match top_item {
-            Some(contents) => output_it(vec![vec!["Now", contents]]),
-            None => {
-                if output.is_nonquiet_for_humans() {
-                    output_it(vec![vec!["Now", "NOTHING"]])
-                } else {
-                    output_it(vec![])
-                }
-            }
-        }
+/// Copy the item at position `from` as a new item, and put the copy below
+/// the `to` most-current items. Positions skip snoozed items. Nothing happens
+/// if there aren't enough items.
+fn copy_item(
+    stack: String,
+    from: usize,
+    to: usize,
+    backend: &dyn Backend,
+) -> Result<EffectResult, SigiError> {
+    let mut items = backend.load(&stack)?;
+    let current = current_indices(&items);
+    if from >= current.len() || to > current.len() {
+        let top = vec![];
+        return Ok(EffectResult::Reordered { stack, top });
     }
+
+    let item = items[current[from]].duplicate();
+    let at = match to {
+        0 => items.len(),
+        to => current[to - 1],
+    };
+    items.insert(at, item.clone());
+    backend.save(&stack, items)?;
+
+    Ok(EffectResult::Pushed { stack, item })
 }
 
-fn count_all_items(stack: String, backend: &Backend, output: &OutputFormat) {
-    if let OutputFormat::Silent = output {
-        return;
+fn delete_second_item(stack: String, backend: &dyn Backend) -> Result<EffectResult, SigiError> {
+    let mut items = backend.load(&stack)?;
+    let current = current_indices(&items);
+    if current.len() < 2 {
+        let now = current_item(&items);
+        return Ok(EffectResult::Deleted {
+            stack,
+            item: None,
+            now,
+        });
     }
 
-    if let Ok(items) = backend.load(&stack) {
-        let len = items.len().to_string();
-        output.log_always(vec!["items"], vec![vec![&len]])
+    let mut item = items.remove(current[1]);
+    item.mark_deleted();
+    // Push the now-marked-deleted item to history stack.
+    backend.push(&stack_history_of(&stack), item.clone())?;
+
+    let now = current_item(&items);
+    backend.save(&stack, items)?;
+
+    Ok(EffectResult::Deleted {
+        stack,
+        item: Some(item),
+        now,
+    })
+}
+
+fn show_item(
+    stack: String,
+    item: ItemRef,
+    backend: &dyn Backend,
+) -> Result<EffectResult, SigiError> {
+    let items = backend.load(&stack)?;
+    let item = item.position_in(&items).map(|position| {
+        let index = items.len() - 1 - position;
+        (position, items[index].clone())
+    });
+    Ok(EffectResult::Shown { stack, item })
+}
+
+fn edit_item(
+    stack: String,
+    item: ItemRef,
+    contents: String,
+    notes: String,
+    backend: &dyn Backend,
+) -> Result<EffectResult, SigiError> {
+    update_item(stack, item, backend, |_| (contents, notes))
+}
+
+fn amend_item(
+    stack: String,
+    item: ItemRef,
+    amendment: Amendment,
+    backend: &dyn Backend,
+) -> Result<EffectResult, SigiError> {
+    update_item(stack, item, backend, |item| {
+        (amendment.apply(&item.contents), item.notes.clone())
+    })
+}
+
+/// Give an item new contents and notes, if it's there and they're different.
+/// Items can't be emptied this way.
+fn update_item(
+    stack: String,
+    item: ItemRef,
+    backend: &dyn Backend,
+    change: impl FnOnce(&Item) -> (String, String),
+) -> Result<EffectResult, SigiError> {
+    let mut items = backend.load(&stack)?;
+    let index = match item.position_in(&items) {
+        Some(position) => items.len() - 1 - position,
+        None => return Ok(EffectResult::Edited { stack, item: None }),
+    };
+
+    let item = &mut items[index];
+    let (contents, notes) = change(item);
+    let unchanged = item.contents == contents && item.notes == notes;
+    if unchanged || contents.trim().is_empty() {
+        return Ok(EffectResult::Edited { stack, item: None });
     }
+    item.mark_edited(contents, notes);
+    let item = item.clone();
+    backend.save(&stack, items)?;
+
+    Ok(EffectResult::Edited {
+        stack,
+        item: Some(item),
+    })
 }
 
-fn is_empty(stack: String, backend: &Backend, output: &OutputFormat) {
-    if let Ok(items) = backend.load(&stack) {
-        if !items.is_empty() {
-            output.log_always(vec!["empty"], vec![vec!["false"]]);
-            // Exit with a failure (nonzero status) when not empty.
-            // This helps people who do shell scripting do something like:
-            //     while ! sigi -t $stack is-empty ; do <ETC> ; done
-            // TODO: It would be better modeled as an error, if anyone uses as a lib this will surprise.
-            if let OutputFormat::TerseText = output {
-                return;
-            } else {
-                std::process::exit(1);
-            }
+fn snooze_latest_item(
+    stack: String,
+    until: DateTime<Local>,
+    backend: &dyn Backend,
+) -> Result<EffectResult, SigiError> {
+    let mut items = backend.load(&stack)?;
+    let item = match current_indices(&items).first() {
+        Some(&i) => {
+            let mut item = items.remove(i);
+            item.mark_snoozed(until);
+            items.insert(0, item.clone());
+            Some(item)
         }
-    }
-    output.log_always(vec!["empty"], vec![vec!["true"]]);
+        None => None,
+    };
+
+    let now = current_item(&items);
+    backend.save(&stack, items)?;
+
+    Ok(EffectResult::Snoozed { stack, item, now })
 }
 
-fn list_stacks(backend: &Backend, output: &OutputFormat) {
-    if let Ok(stacks) = backend.list_stacks() {
-        let mut stacks = stacks;
-        stacks.sort();
-        let strs = stacks.iter().map(|stack| vec![stack.as_str()]).collect();
-        output.log_always(vec!["stack"], strs);
+/// Put items back on top of the stack once their snooze is over, the first
+/// to wake up ending on top.
+fn wake_snoozed_items(stack: &str, backend: &dyn Backend) -> Result<(), SigiError> {
+    let now = Local::now();
+    let items = backend.load(stack)?;
+    let is_awake = |item: &Item| item.snoozed_until.is_some_and(|until| until <= now);
+    if !items.iter().any(is_awake) {
+        return Ok(());
+    }
+
+    let (mut woken, mut items): (Stack, Stack) = items.into_iter().partition(is_awake);
+    woken.sort_by_key(|item| std::cmp::Reverse(item.snoozed_until));
+    for mut item in woken {
+        item.snoozed_until = None;
+        items.push(item);
     }
+    backend.save(stack, items)
 }
 
-// ===== ListAll/Head/Tail =====
+fn next_to_latest(stack: String, backend: &dyn Backend) -> Result<EffectResult, SigiError> {
+    let mut items = backend.load(&stack)?;
+    let Some(&i) = current_indices(&items).first() else {
+        return Ok(EffectResult::Cycled { stack, now: None });
+    };
+    let to_the_back = items.remove(i);
+    items.insert(0, to_the_back);
 
-trait Listable {
-    fn range(self) -> ListRange;
+    let now = current_item(&items);
+    backend.save(&stack, items)?;
+
+    Ok(EffectResult::Cycled { stack, now })
 }
 
-struct ListRange {
+fn peek_latest_item(
     stack: String,
-    // Ignored if starting "from_end".
-    start: usize,
-    limit: Option<usize>,
-    from_end: bool,
+    view: View,
+    backend: &dyn Backend,
+) -> Result<EffectResult, SigiError> {
+    let at = Local::now();
+    let now = backend
+        .load(&stack)?
+        .into_iter()
+        .rev()
+        .find(|item| view.shows(item, at));
+    Ok(EffectResult::Peeked { stack, now })
 }
 
-fn list_range(range: ListRange, backend: &Backend, output: &OutputFormat) {
-    if let OutputFormat::Silent = output {
-        return;
-    }
+fn count_all_items(
+    stack: String,
+    tags: Tags,
+    backend: &dyn Backend,
+) -> Result<EffectResult, SigiError> {
+    let items = backend.load(&stack)?;
+    let count = items.iter().filter(|item| item.has_tags(&tags)).count();
+    Ok(EffectResult::Counted { stack, count })
+}
 
-    if let Ok(items) = backend.load(&range.stack) {
-        let limit = match range.limit {
-            Some(n) => n,
-            None => items.len(),
-        };
+fn is_empty(stack: String, backend: &dyn Backend) -> Result<EffectResult, SigiError> {
+    let empty = backend.load(&stack)?.is_empty();
+    Ok(EffectResult::IsEmpty { stack, empty })
+}
 
-        let start = if range.from_end {
-            if limit <= items.len() {
-                items.len() - limit
-            } else {
-                0
-            }
-        } else {
-            range.start
-        };
+fn list_due_items(overdue: bool, backend: &dyn Backend) -> Result<EffectResult, SigiError> {
+    let now = Local::now();
+    let mut items = vec![];
+    for stack in backend.list_stacks()? {
+        // Completed and deleted items aren't due anymore.
+        if stack.ends_with(HISTORY_SUFFIX) {
+            continue;
+        }
+        let stack_items = backend.load(&stack)?;
+        let due = stack_items
+            .into_iter()
+            .rev()
+            .enumerate()
+            .filter(|(_, item)| item.due.is_some() && (!overdue || item.is_overdue(now)))
+            .map(|(position, item)| StackItem {
+                stack: stack.clone(),
+                position,
+                item,
+            });
+        items.extend(due);
+    }
+    items.sort_by(|a, b| {
+        (a.item.due, &a.stack, a.position).cmp(&(b.item.due, &b.stack, b.position))
+    });
+    Ok(EffectResult::Due { items, overdue })
+}
 
-        let lines = items
+fn find_items(
+    stack: String,
+    search: Search,
+    backend: &dyn Backend,
+) -> Result<EffectResult, SigiError> {
+    let mut stacks = if search.all_stacks {
+        backend
+            .list_stacks()?
+            .into_iter()
+            .filter(|stack| search.history || !stack.ends_with(HISTORY_SUFFIX))
+            .collect()
+    } else if search.history {
+        vec![stack_history_of(&stack), stack]
+    } else {
+        vec![stack]
+    };
+    stacks.sort();
+    stacks.dedup();
+
+    let mut items = vec![];
+    for stack in stacks {
+        let found = backend
+            .load(&stack)?
             .into_iter()
             .rev()
             .enumerate()
-            .skip(start)
-            .take(limit)
-            .map(|(i, item)| {
-                // Pad human output numbers to line up nicely with "Now".
-                let position = if output.is_nonquiet_for_humans() {
-                    match i {
-                        0 => "Now".to_string(),
-                        1..=9 => format!("  {}", i),
-                        10..=99 => format!(" {}", i),
-                        _ => i.to_string(),
-                    }
-                } else {
-                    i.to_string()
-                };
+            .filter(|(_, item)| search.pattern.is_match(&item.contents))
+            .map(|(position, item)| StackItem {
+                stack: stack.clone(),
+                position,
+                item,
+            });
+        items.extend(found);
+    }
+    Ok(EffectResult::Found { items })
+}
 
-                let created = item
-                    .history
-                    .iter()
-                    .find(|(status, _)| status == "created")
-                    .map(|(_, dt)| output.format_time(*dt))
-                    .unwrap_or_else(|| "unknown".to_string());
+fn list_stacks(backend: &dyn Backend) -> Result<EffectResult, SigiError> {
+    let mut stacks = backend.list_stacks()?;
+    stacks.sort();
+    Ok(EffectResult::Stacks { stacks })
+}
 
-                vec![position, item.contents, created]
-            })
-            .collect::<Vec<_>>();
+// ===== ListAll/Head/Tail =====
 
-        let labels = vec!["position", "item", "created"];
+struct ListRange {
+    stack: String,
+    view: View,
+    limit: Option<usize>,
+    from_end: bool,
+}
 
-        if lines.is_empty() {
-            if output.is_nonquiet_for_humans() {
-                output.log(labels, vec![vec!["Now", "NOTHING"]]);
-            }
-            return;
-        }
+fn list_range(range: ListRange, backend: &dyn Backend) -> Result<EffectResult, SigiError> {
+    let now = Local::now();
+    let items: Vec<(usize, Item)> = backend
+        .load(&range.stack)?
+        .into_iter()
+        .rev()
+        .enumerate()
+        .filter(|(_, item)| range.view.shows(item, now))
+        .collect();
+    let limit = match range.limit {
+        Some(n) => n,
+        None => items.len(),
+    };
 
-        // Get the lines into a "borrow" state (&str instead of String) to make log happy.
-        let lines = lines
-            .iter()
-            .map(|line| line.iter().map(|s| s.as_str()).collect())
-            .collect();
+    let start = if range.from_end {
+        items.len().saturating_sub(limit)
+    } else {
+        0
+    };
 
-        output.log_always(labels, lines);
-    }
+    let items = items.into_iter().skip(start).take(limit).collect();
+
+    Ok(EffectResult::Listed {
+        stack: range.stack,
+        items,
+    })
 }
 
-fn list_all_items(stack: String, backend: &Backend, output: &OutputFormat) {
+fn list_all_items(
+    stack: String,
+    view: View,
+    backend: &dyn Backend,
+) -> Result<EffectResult, SigiError> {
     let range = ListRange {
         stack,
-        start: 0,
+        view,
         limit: None,
         from_end: false,
     };
 
-    list_range(range, backend, output);
+    list_range(range, backend)
 }
 
-fn list_n_latest_items(stack: String, n: usize, backend: &Backend, output: &OutputFormat) {
+fn list_n_latest_items(
+    stack: String,
+    n: usize,
+    view: View,
+    backend: &dyn Backend,
+) -> Result<EffectResult, SigiError> {
     let range = ListRange {
         stack,
-        start: 0,
+        view,
         limit: Some(n),
         from_end: false,
     };
 
-    list_range(range, backend, output);
+    list_range(range, backend)
 }
 
-fn list_n_oldest_items(stack: String, n: usize, backend: &Backend, output: &OutputFormat) {
+fn list_n_oldest_items(
+    stack: String,
+    n: usize,
+    view: View,
+    backend: &dyn Backend,
+) -> Result<EffectResult, SigiError> {
     let range = ListRange {
         stack,
-        start: 0,
+        view,
         limit: Some(n),
         from_end: true,
     };
 
-    list_range(range, backend, output);
+    list_range(range, backend)
+}
+
+// ===== Output =====
+
+impl EffectResult {
+    /// Print the result in the given format.
+    pub fn log(&self, output: &OutputFormat) {
+        use EffectResult::*;
+        match self {
+            Pushed { item, .. } => {
+                let tags = item.tags.join(" ");
+                let [due, _] = due_columns(item, output);
+                output.log(
+                    vec!["action", "item", "id", "tags", "due"],
+                    vec![vec!["Created", &item.contents, &item.id, &tags, &due]],
+                );
+            }
+            Completed {
+                item, next, now, ..
+            } => {
+                if let Some(item) = item {
+                    output.log(
+                        vec!["action", "item", "id"],
+                        vec![vec!["Completed", &item.contents, &item.id]],
+                    );
+                }
+                if let Some(next) = next {
+                    let due = next
+                        .due
+                        .map(|due| output.format_time(due))
+                        .unwrap_or_default();
+                    output.log(
+                        vec!["action", "item", "id", "due"],
+                        vec![vec!["Repeats", &next.contents, &next.id, &due]],
+                    );
+                }
+                if output.is_nonquiet_for_humans() {
+                    log_now(now.as_ref(), output);
+                }
+            }
+            Deleted { item, now, .. } => {
+                if let Some(item) = item {
+                    output.log(
+                        vec!["action", "item", "id"],
+                        vec![vec!["Deleted", &item.contents, &item.id]],
+                    );
+                }
+                if output.is_nonquiet_for_humans() {
+                    log_now(now.as_ref(), output);
+                }
+            }
+            DeletedAll { count, .. } => {
+                output.log(
+                    vec!["action", "item"],
+                    vec![vec!["Deleted", &format!("{} items", count)]],
+                );
+            }
+            Reordered { top, .. } => {
+                if !top.is_empty() && output.is_nonquiet_for_humans() {
+                    let top: Vec<_> = top.iter().cloned().enumerate().collect();
+                    log_items(&top, output);
+                }
+            }
+            Snoozed { item, now, .. } => {
+                if let Some(item) = item {
+                    let until = item
+                        .snoozed_until
+                        .map(|until| output.format_time(until))
+                        .unwrap_or_default();
+                    output.log(
+                        vec!["action", "item", "until", "id"],
+                        vec![vec!["Snoozed", &item.contents, &until, &item.id]],
+                    );
+                }
+                if output.is_nonquiet_for_humans() {
+                    log_now(now.as_ref(), output);
+                }
+            }
+            Cycled { now, .. } => {
+                if now.is_some() && output.is_nonquiet_for_humans() {
+                    log_now(now.as_ref(), output);
+                }
+            }
+            Moved { stack, dest, item } => {
+                if item.is_some() {
+                    output.log(
+                        vec!["action", "new-stack", "old-stack"],
+                        vec![vec!["Move", dest, stack]],
+                    );
+                }
+            }
+            MovedAll { stack, dest, count } => {
+                output.log(
+                    vec!["action", "new-stack", "old-stack", "num-moved"],
+                    vec![vec!["Move All", dest, stack, &count.to_string()]],
+                );
+            }
+            Peeked { now, .. } => log_now(now.as_ref(), output),
+            Shown { item, .. } => log_shown(item.as_ref(), output),
+            Edited { item, .. } => match item {
+                Some(item) => output.log(
+                    vec!["action", "item", "id"],
+                    vec![vec!["Edited", &item.contents, &item.id]],
+                ),
+                None => {
+                    if output.is_nonquiet_for_humans() {
+                        output.log(vec!["action", "item"], vec![vec!["Edited", "NOTHING"]]);
+                    }
+                }
+            },
+            Listed { items, .. } => log_items(items, output),
+            Stacks { stacks } => {
+                let strs = stacks.iter().map(|stack| vec![stack.as_str()]).collect();
+                output.log_always(vec!["stack"], strs);
+            }
+            Counted { count, .. } => {
+                if let OutputFormat::Silent = output {
+                    return;
+                }
+                output.log_always(vec!["items"], vec![vec![&count.to_string()]]);
+            }
+            IsEmpty { empty, .. } => {
+                output.log_always(vec!["empty"], vec![vec![&empty.to_string()]]);
+            }
+            Due { items, overdue } => log_due(items, *overdue, output),
+            Found { items } => log_found(items, output),
+            Restored { items, .. } => {
+                let labels = vec!["action", "item", "id"];
+                if items.is_empty() {
+                    if output.is_nonquiet_for_humans() {
+                        output.log(labels, vec![vec!["Restored", "NOTHING"]]);
+                    }
+                    return;
+                }
+                let lines = items
+                    .iter()
+                    .map(|item| vec!["Restored", item.contents.as_str(), item.id.as_str()])
+                    .collect();
+                output.log(labels, lines);
+            }
+            History { events, .. } => log_history(events, output),
+            Pruned { items, dry_run, .. } => {
+                let action = if *dry_run { "Would prune" } else { "Pruned" };
+                let labels = vec!["action", "item", "id"];
+                if items.is_empty() {
+                    if output.is_nonquiet_for_humans() {
+                        output.log(labels, vec![vec![action, "NOTHING"]]);
+                    }
+                    return;
+                }
+                let lines = items
+                    .iter()
+                    .map(|item| vec![action, item.contents.as_str(), item.id.as_str()])
+                    .collect();
+                output.log(labels, lines);
+            }
+            RetentionPolicy { policy, .. } => {
+                let or_none = |n: Option<String>| n.unwrap_or_else(|| "none".to_string());
+                let max_age_days = or_none(policy.max_age_days.map(|n| n.to_string()));
+                let max_count = or_none(policy.max_count.map(|n| n.to_string()));
+                let auto_prune = policy.auto_prune.to_string();
+                output.log_always(
+                    vec!["setting", "value"],
+                    vec![
+                        vec!["max-age-days", &max_age_days],
+                        vec!["max-count", &max_count],
+                        vec!["auto-prune", &auto_prune],
+                    ],
+                );
+            }
+            Undone(entries) => log_journal_entries("Undone", entries, output),
+            Redone(entries) => log_journal_entries("Redone", entries, output),
+        }
+    }
+}
+
+fn log_now(now: Option<&Item>, output: &OutputFormat) {
+    if let OutputFormat::Silent = output {
+        return;
+    }
+
+    let labels = vec!["position", "item", "id", "tags", "due", "overdue"];
+    let output_it = |it| output.log_always(labels, it);
+
+    match now {
+        Some(item) => {
+            let contents = item_text(item, output);
+            let tags = item.tags.join(" ");
+            let [due, overdue] = due_columns(item, output);
+            output_it(vec![vec![
+                "Now", &contents, &item.id, &tags, &due, &overdue,
+            ]])
+        }
+        None => {
+            if output.is_nonquiet_for_humans() {
+                output_it(vec![vec!["Now", "NOTHING"]])
+            } else {
+                output_it(vec![])
+            }
+        }
+    }
+}
+
+/// An item's contents and notes in full. Humans get the notes as indented
+/// lines under the item.
+fn log_shown(item: Option<&(usize, Item)>, output: &OutputFormat) {
+    if let OutputFormat::Silent = output {
+        return;
+    }
+
+    let labels = vec!["position", "item", "notes", "id", "tags", "due"];
+    let (position, item) = match item {
+        Some((position, item)) => (*position, item),
+        None => {
+            if output.is_nonquiet_for_humans() {
+                output.log_always(labels, vec![vec!["Now", "NOTHING"]]);
+            }
+            return;
+        }
+    };
+
+    let position = if position == 0 && output.is_nonquiet_for_humans() {
+        "Now".to_string()
+    } else {
+        position.to_string()
+    };
+    let contents = item_text(item, output);
+    match output {
+        OutputFormat::Human(_) | OutputFormat::TerseText => {
+            let notes: Vec<String> = item
+                .notes
+                .lines()
+                .map(|line| format!("  {}", line))
+                .collect();
+            let mut lines = vec![vec![position.as_str(), &contents]];
+            lines.extend(notes.iter().map(|line| vec![line.as_str()]));
+            output.log_always(labels, lines);
+        }
+        _ => {
+            let tags = item.tags.join(" ");
+            let due = item
+                .due
+                .map(|due| output.format_time(due))
+                .unwrap_or_default();
+            output.log_always(
+                labels,
+                vec![vec![
+                    &position,
+                    &contents,
+                    &item.notes,
+                    &item.id,
+                    &tags,
+                    &due,
+                ]],
+            );
+        }
+    }
+}
+
+/// An item's contents, flagged for humans if it's overdue or snoozed.
+fn item_text(item: &Item, output: &OutputFormat) -> String {
+    let mut text = item.contents.clone();
+    if output.is_nonquiet_for_humans() {
+        let now = Local::now();
+        if item.is_overdue(now) {
+            text.push_str(" (OVERDUE)");
+        }
+        if item.is_snoozed(now) {
+            text.push_str(" (SNOOZED)");
+        }
+    }
+    text
+}
+
+/// The "due" and "overdue" columns for an item. Humans see overdue items
+/// flagged in their contents instead. (See [`item_text`])
+fn due_columns(item: &Item, output: &OutputFormat) -> [String; 2] {
+    let due = item
+        .due
+        .map(|due| output.format_time(due))
+        .unwrap_or_default();
+    let overdue = if output.is_nonquiet_for_humans() {
+        String::new()
+    } else {
+        item.is_overdue(Local::now()).to_string()
+    };
+    [due, overdue]
+}
+
+fn log_found(items: &[StackItem], output: &OutputFormat) {
+    if let OutputFormat::Silent = output {
+        return;
+    }
+
+    let labels = vec!["stack", "item", "position", "id"];
+
+    if items.is_empty() {
+        if output.is_nonquiet_for_humans() {
+            output.log(labels, vec![vec!["Found", "NOTHING"]]);
+        }
+        return;
+    }
+
+    let lines = items
+        .iter()
+        .map(
+            |StackItem {
+                 stack,
+                 position,
+                 item,
+             }| {
+                // Humans see where each item is first, e.g. "work 2: item".
+                let location = if !output.is_nonquiet_for_humans() {
+                    stack.clone()
+                } else if *position == 0 {
+                    format!("{} Now", stack)
+                } else {
+                    format!("{} {}", stack, position)
+                };
+                vec![
+                    location,
+                    item_text(item, output),
+                    position.to_string(),
+                    item.id.clone(),
+                ]
+            },
+        )
+        .collect::<Vec<_>>();
+    let lines = lines
+        .iter()
+        .map(|line| line.iter().map(|s| s.as_str()).collect())
+        .collect();
+
+    output.log_always(labels, lines);
+}
+
+fn log_due(items: &[StackItem], overdue: bool, output: &OutputFormat) {
+    if let OutputFormat::Silent = output {
+        return;
+    }
+
+    let labels = vec!["due", "item", "stack", "position", "overdue", "id"];
+
+    if items.is_empty() {
+        if output.is_nonquiet_for_humans() {
+            let title = if overdue { "Overdue" } else { "Due" };
+            output.log(labels, vec![vec![title, "NOTHING"]]);
+        }
+        return;
+    }
+
+    let lines = items
+        .iter()
+        .map(
+            |StackItem {
+                 stack,
+                 position,
+                 item,
+             }| {
+                let [due, overdue] = due_columns(item, output);
+                vec![
+                    due,
+                    item_text(item, output),
+                    stack.clone(),
+                    position.to_string(),
+                    overdue,
+                    item.id.clone(),
+                ]
+            },
+        )
+        .collect::<Vec<_>>();
+    let lines = lines
+        .iter()
+        .map(|line| line.iter().map(|s| s.as_str()).collect())
+        .collect();
+
+    output.log_always(labels, lines);
+}
+
+fn log_items(items: &[(usize, Item)], output: &OutputFormat) {
+    if let OutputFormat::Silent = output {
+        return;
+    }
+
+    let lines = items
+        .iter()
+        .map(|(i, item)| {
+            let i = *i;
+            // Pad human output numbers to line up nicely with "Now".
+            let position = if output.is_nonquiet_for_humans() {
+                match i {
+                    0 => "Now".to_string(),
+                    1..=9 => format!("  {}", i),
+                    10..=99 => format!(" {}", i),
+                    _ => i.to_string(),
+                }
+            } else {
+                i.to_string()
+            };
+
+            let created = item
+                .history
+                .iter()
+                .find(|event| event.name == "created")
+                .map(|event| output.format_time(event.at))
+                .unwrap_or_else(|| "unknown".to_string());
+
+            let [due, overdue] = due_columns(item, output);
+            let snoozed = item
+                .snoozed_until
+                .map(|until| output.format_time(until))
+                .unwrap_or_default();
+            let recurrence = item
+                .recurrence
+                .as_ref()
+                .map(|recurrence| recurrence.to_string())
+                .unwrap_or_default();
+
+            vec![
+                position,
+                item_text(item, output),
+                created,
+                item.id.clone(),
+                item.tags.join(" "),
+                due,
+                overdue,
+                snoozed,
+                recurrence,
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let labels = vec![
+        "position",
+        "item",
+        "created",
+        "id",
+        "tags",
+        "due",
+        "overdue",
+        "snoozed",
+        "recurrence",
+    ];
+
+    if lines.is_empty() {
+        if output.is_nonquiet_for_humans() {
+            output.log(labels, vec![vec!["Now", "NOTHING"]]);
+        }
+        return;
+    }
+
+    // Get the lines into a "borrow" state (&str instead of String) to make log happy.
+    let lines = lines
+        .iter()
+        .map(|line| line.iter().map(|s| s.as_str()).collect())
+        .collect();
+
+    output.log_always(labels, lines);
+}
+
+fn log_history(events: &[HistoryEvent], output: &OutputFormat) {
+    if let OutputFormat::Silent = output {
+        return;
+    }
+
+    let labels = vec!["event", "item", "at", "id", "series", "detail"];
+
+    if events.is_empty() {
+        if output.is_nonquiet_for_humans() {
+            output.log(labels, vec![vec!["History", "NOTHING"]]);
+        }
+        return;
+    }
+
+    let lines = events
+        .iter()
+        .map(|event| {
+            vec![
+                event.event.clone(),
+                event.item.clone(),
+                output.format_time(event.at),
+                event.id.clone(),
+                event.series.clone().unwrap_or_default(),
+                event.detail.clone().unwrap_or_default(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let lines = lines
+        .iter()
+        .map(|line| line.iter().map(|s| s.as_str()).collect())
+        .collect();
+
+    // When something happened is the point of history, so always show it to humans.
+    let output = if output.is_nonquiet_for_humans() {
+        &OutputFormat::Human(NoiseLevel::Verbose)
+    } else {
+        output
+    };
+    output.log_always(labels, lines);
+}
+
+fn log_journal_entries(action: &str, entries: &[JournalEntry], output: &OutputFormat) {
+    let labels = vec!["action", "effect", "stack"];
+
+    if entries.is_empty() {
+        if output.is_nonquiet_for_humans() {
+            output.log(labels, vec![vec![action, "NOTHING"]]);
+        }
+        return;
+    }
+
+    let lines = entries
+        .iter()
+        .map(|entry| vec![action, &entry.effect, &entry.stack])
+        .collect();
+    output.log(labels, lines);
 }
 
 // ===== Helper functions =====
@@ -469,3 +1862,45 @@ fn list_n_oldest_items(stack: String, n: usize, backend: &Backend, output: &Outp
 fn stack_history_of(stack: &str) -> String {
     stack.to_string() + HISTORY_SUFFIX
 }
+
+fn require_stack(stack: &str, backend: &dyn Backend) -> Result<(), SigiError> {
+    if backend.list_stacks()?.iter().any(|name| name == stack) {
+        Ok(())
+    } else {
+        Err(SigiError::UnknownStack(stack.to_string()))
+    }
+}
+
+/// The indices of items that aren't snoozed, most recent first. The first is
+/// the current item, the one `peek` shows, and the one effects on "the
+/// current item" act on.
+fn current_indices(items: &[Item]) -> Vec<usize> {
+    let now = Local::now();
+    (0..items.len())
+        .rev()
+        .filter(|&i| !items[i].is_snoozed(now))
+        .collect()
+}
+
+fn current_item(items: &[Item]) -> Option<Item> {
+    current_indices(items).first().map(|&i| items[i].clone())
+}
+
+/// Take the current item off the stack, if there is one.
+fn take_current_item(stack: &str, backend: &dyn Backend) -> Result<Option<Item>, SigiError> {
+    let mut items = backend.load(stack)?;
+    match current_indices(&items).first() {
+        Some(&i) if i + 1 == items.len() => backend.pop(stack),
+        Some(&i) => {
+            let item = items.remove(i);
+            backend.save(stack, items)?;
+            Ok(Some(item))
+        }
+        None => Ok(None),
+    }
+}
+
+/// The latest `n` items, most recent first.
+fn latest_n(items: &[Item], n: usize) -> Vec<Item> {
+    items.iter().rev().take(n).cloned().collect()
+}
@@ -0,0 +1,411 @@
+use crate::data::{Backend, Item, Lock, Stack};
+use crate::error::SigiError;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+pub(super) const JOURNAL_KEY: &str = "journal";
+
+/// How many effects can be undone. Older entries are forgotten.
+const JOURNAL_LIMIT: usize = 100;
+
+/// Effects that can be undone, and effects that were undone and can be redone.
+/// Both are oldest first.
+#[derive(Serialize, Deserialize, Default)]
+struct Journal {
+    undo: Vec<JournalEntry>,
+    redo: Vec<JournalEntry>,
+}
+
+/// One effect's changes to the store, in the order they were made.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JournalEntry {
+    /// The name of the effect, e.g. "delete-all".
+    pub effect: String,
+    /// The stack the effect was run on.
+    pub stack: String,
+    changes: Vec<Change>,
+}
+
+/// A single change made to the store through a [`Backend`]. Each one holds
+/// enough to be reverted. Items are found by ID, not by position, so changes
+/// can be reverted even if the stack has changed in other ways since.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "change", rename_all = "kebab-case")]
+enum Change {
+    Push {
+        stack: String,
+        item: Item,
+    },
+    Append {
+        stack: String,
+        items: Stack,
+    },
+    Pop {
+        stack: String,
+        item: Item,
+    },
+    Update {
+        stack: String,
+        #[serde(flatten)]
+        diff: Diff,
+    },
+}
+
+impl Change {
+    fn apply(&self, backend: &dyn Backend) -> Result<(), SigiError> {
+        match self {
+            Change::Push { stack, item } => backend.push(stack, item.clone()),
+            Change::Append { stack, items } => backend.append(stack, items.clone()),
+            Change::Pop { stack, item } => remove_items(backend, stack, std::slice::from_ref(item)),
+            Change::Update { stack, diff } => diff.apply(backend, stack),
+        }
+    }
+
+    fn revert(&self, backend: &dyn Backend) -> Result<(), SigiError> {
+        match self {
+            Change::Push { stack, item } => {
+                remove_items(backend, stack, std::slice::from_ref(item))
+            }
+            Change::Append { stack, items } => remove_items(backend, stack, items),
+            Change::Pop { stack, item } => backend.push(stack, item.clone()),
+            Change::Update { stack, diff } => diff.revert(backend, stack),
+        }
+    }
+}
+
+/// Take items out of a stack by ID, the most recent first.
+fn remove_items(backend: &dyn Backend, stack: &str, items: &[Item]) -> Result<(), SigiError> {
+    let mut all_items = backend.load(stack)?;
+    for item in items {
+        if let Some(i) = find_item(&all_items, item, all_items.len()) {
+            all_items.remove(i);
+        }
+    }
+    backend.save(stack, all_items)
+}
+
+/// The index of the item with the same ID as `item`. If there's more than
+/// one, the one closest to `near`.
+fn find_item(items: &[Item], item: &Item, near: usize) -> Option<usize> {
+    items
+        .iter()
+        .enumerate()
+        .filter(|(_, other)| other.id == item.id)
+        .min_by_key(|(i, _)| i.abs_diff(near))
+        .map(|(i, _)| i)
+}
+
+/// An item and its index in a stack, oldest first.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Placed {
+    at: usize,
+    item: Item,
+}
+
+/// How a stack was saved differently: only the items that were taken out,
+/// put in, moved, or changed in place. A moved item is both taken out and put
+/// in.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct Diff {
+    /// Items that were taken out, where they were before.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    removed: Vec<Placed>,
+    /// Items that were put in, where they are after.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    added: Vec<Placed>,
+    /// Items that stayed in place but changed, before and after.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    changed: Vec<(Item, Item)>,
+}
+
+impl Diff {
+    fn between(before: &[Item], after: &[Item]) -> Self {
+        let mut before_index: HashMap<(&str, usize), usize> = keys(before)
+            .into_iter()
+            .enumerate()
+            .map(|(i, key)| (key, i))
+            .collect();
+
+        let mut diff = Diff::default();
+        // For items in both, where they were before, in their order after.
+        let mut kept: Vec<(usize, usize)> = vec![];
+        for (at, (item, key)) in after.iter().zip(keys(after)).enumerate() {
+            match before_index.remove(&key) {
+                Some(i) => kept.push((i, at)),
+                None => diff.added.push(Placed {
+                    at,
+                    item: item.clone(),
+                }),
+            }
+        }
+        diff.removed = before_index
+            .into_values()
+            .map(|at| Placed {
+                at,
+                item: before[at].clone(),
+            })
+            .collect();
+
+        // The most items that kept their order stayed in place; the rest moved.
+        let stayed = longest_increasing(&kept);
+        for (n, &(i, at)) in kept.iter().enumerate() {
+            if stayed.contains(&n) {
+                if before[i] != after[at] {
+                    diff.changed.push((before[i].clone(), after[at].clone()));
+                }
+            } else {
+                diff.removed.push(Placed {
+                    at: i,
+                    item: before[i].clone(),
+                });
+                diff.added.push(Placed {
+                    at,
+                    item: after[at].clone(),
+                });
+            }
+        }
+        diff.removed.sort_by_key(|placed| placed.at);
+        diff.added.sort_by_key(|placed| placed.at);
+        diff
+    }
+
+    fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty() && self.changed.is_empty()
+    }
+
+    fn apply(&self, backend: &dyn Backend, stack: &str) -> Result<(), SigiError> {
+        let changed = self.changed.iter().map(|(before, after)| (before, after));
+        patch(backend, stack, &self.removed, changed, &self.added)
+    }
+
+    fn revert(&self, backend: &dyn Backend, stack: &str) -> Result<(), SigiError> {
+        let changed = self.changed.iter().map(|(before, after)| (after, before));
+        patch(backend, stack, &self.added, changed, &self.removed)
+    }
+}
+
+/// What matches items up between stacks: their IDs, and which one it is
+/// should IDs repeat.
+fn keys(items: &[Item]) -> Vec<(&str, usize)> {
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    items
+        .iter()
+        .map(|item| {
+            let n = seen.entry(&item.id).or_default();
+            *n += 1;
+            (item.id.as_str(), *n)
+        })
+        .collect()
+}
+
+/// Take out some items, change others, then put in some more, each where
+/// they belong.
+fn patch<'a>(
+    backend: &dyn Backend,
+    stack: &str,
+    take_out: &[Placed],
+    change: impl Iterator<Item = (&'a Item, &'a Item)>,
+    put_in: &[Placed],
+) -> Result<(), SigiError> {
+    let mut items = backend.load(stack)?;
+    for placed in take_out.iter().rev() {
+        if let Some(i) = find_item(&items, &placed.item, placed.at) {
+            items.remove(i);
+        }
+    }
+    for (from, to) in change {
+        if let Some(i) = find_item(&items, from, items.len()) {
+            items[i] = to.clone();
+        }
+    }
+    for placed in put_in {
+        let at = placed.at.min(items.len());
+        items.insert(at, placed.item.clone());
+    }
+    backend.save(stack, items)
+}
+
+/// The indices of a longest run of pairs whose first values only go up.
+fn longest_increasing(pairs: &[(usize, usize)]) -> HashSet<usize> {
+    // Patience sorting: `tails[k]` ends the best run of length k + 1 so far.
+    let mut tails: Vec<usize> = vec![];
+    let mut previous: Vec<Option<usize>> = vec![None; pairs.len()];
+    for (n, &(value, _)) in pairs.iter().enumerate() {
+        let k = tails.partition_point(|&t| pairs[t].0 < value);
+        previous[n] = k.checked_sub(1).map(|k| tails[k]);
+        if k == tails.len() {
+            tails.push(n);
+        } else {
+            tails[k] = n;
+        }
+    }
+
+    let mut run = HashSet::new();
+    let mut next = tails.last().copied();
+    while let Some(n) = next {
+        run.insert(n);
+        next = previous[n];
+    }
+    run
+}
+
+/// A backend that passes everything through to another one, and remembers
+/// each change so it can be journaled.
+pub(super) struct Recorder<'a> {
+    backend: &'a dyn Backend,
+    changes: RefCell<Vec<Change>>,
+}
+
+impl<'a> Recorder<'a> {
+    pub(super) fn new(backend: &'a dyn Backend) -> Self {
+        Recorder {
+            backend,
+            changes: RefCell::new(vec![]),
+        }
+    }
+
+    /// Add the recorded changes to the journal as one entry. Anything that
+    /// could have been redone is forgotten.
+    pub(super) fn finish(self, effect: &str, stack: &str) -> Result<(), SigiError> {
+        let changes = self.changes.into_inner();
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let mut journal = load(self.backend)?;
+        journal.undo.push(JournalEntry {
+            effect: effect.to_string(),
+            stack: stack.to_string(),
+            changes,
+        });
+        let excess = journal.undo.len().saturating_sub(JOURNAL_LIMIT);
+        journal.undo.drain(..excess);
+        journal.redo.clear();
+        save(self.backend, &journal)
+    }
+
+    fn record(&self, change: Change) {
+        self.changes.borrow_mut().push(change);
+    }
+}
+
+impl Backend for Recorder<'_> {
+    fn load(&self, stack_name: &str) -> Result<Stack, SigiError> {
+        self.backend.load(stack_name)
+    }
+
+    fn save(&self, stack_name: &str, items: Stack) -> Result<(), SigiError> {
+        let diff = Diff::between(&self.backend.load(stack_name)?, &items);
+        if diff.is_empty() {
+            return Ok(());
+        }
+        self.backend.save(stack_name, items)?;
+        self.record(Change::Update {
+            stack: stack_name.to_string(),
+            diff,
+        });
+        Ok(())
+    }
+
+    fn list_stacks(&self) -> Result<Vec<String>, SigiError> {
+        self.backend.list_stacks()
+    }
+
+    fn push(&self, stack_name: &str, item: Item) -> Result<(), SigiError> {
+        self.backend.push(stack_name, item.clone())?;
+        self.record(Change::Push {
+            stack: stack_name.to_string(),
+            item,
+        });
+        Ok(())
+    }
+
+    fn pop(&self, stack_name: &str) -> Result<Option<Item>, SigiError> {
+        let item = self.backend.pop(stack_name)?;
+        if let Some(item) = &item {
+            self.record(Change::Pop {
+                stack: stack_name.to_string(),
+                item: item.clone(),
+            });
+        }
+        Ok(item)
+    }
+
+    fn append(&self, stack_name: &str, items: Stack) -> Result<(), SigiError> {
+        if items.is_empty() {
+            return self.backend.append(stack_name, items);
+        }
+        self.backend.append(stack_name, items.clone())?;
+        self.record(Change::Append {
+            stack: stack_name.to_string(),
+            items,
+        });
+        Ok(())
+    }
+
+    fn load_meta(&self, key: &str) -> Result<Option<String>, SigiError> {
+        self.backend.load_meta(key)
+    }
+
+    fn save_meta(&self, key: &str, value: &str) -> Result<(), SigiError> {
+        self.backend.save_meta(key, value)
+    }
+
+    fn lock(&self) -> Result<Lock, SigiError> {
+        self.backend.lock()
+    }
+}
+
+/// Revert the last `n` journaled effects, most recent first.
+pub(super) fn undo(backend: &dyn Backend, n: usize) -> Result<Vec<JournalEntry>, SigiError> {
+    let mut journal = load(backend)?;
+    let mut undone = vec![];
+    for _ in 0..n {
+        let entry = match journal.undo.pop() {
+            Some(entry) => entry,
+            None => break,
+        };
+        for change in entry.changes.iter().rev() {
+            change.revert(backend)?;
+        }
+        journal.redo.push(entry.clone());
+        undone.push(entry);
+    }
+    save(backend, &journal)?;
+    Ok(undone)
+}
+
+/// Apply the last `n` undone effects again, least recently undone last.
+pub(super) fn redo(backend: &dyn Backend, n: usize) -> Result<Vec<JournalEntry>, SigiError> {
+    let mut journal = load(backend)?;
+    let mut redone = vec![];
+    for _ in 0..n {
+        let entry = match journal.redo.pop() {
+            Some(entry) => entry,
+            None => break,
+        };
+        for change in entry.changes.iter() {
+            change.apply(backend)?;
+        }
+        journal.undo.push(entry.clone());
+        redone.push(entry);
+    }
+    save(backend, &journal)?;
+    Ok(redone)
+}
+
+fn load(backend: &dyn Backend) -> Result<Journal, SigiError> {
+    match backend.load_meta(JOURNAL_KEY)? {
+        Some(json) => {
+            serde_json::from_str(&json).map_err(|err| SigiError::corrupt_data(JOURNAL_KEY, err))
+        }
+        None => Ok(Journal::default()),
+    }
+}
+
+fn save(backend: &dyn Backend, journal: &Journal) -> Result<(), SigiError> {
+    let json =
+        serde_json::to_string(journal).map_err(|err| SigiError::corrupt_data(JOURNAL_KEY, err))?;
+    backend.save_meta(JOURNAL_KEY, &json)
+}
@@ -0,0 +1,83 @@
+use crate::data::{Backend, Item, Stack};
+use crate::error::SigiError;
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub(super) const RETENTION_KEY: &str = "retention";
+
+/// How long completed and deleted items are kept in a stack's history stack.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Retention {
+    /// Remove items that went into the history more than this many days ago.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_days: Option<u32>,
+    /// Keep at most this many items, removing the oldest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_count: Option<usize>,
+    /// Prune whenever items are added to the history stack.
+    #[serde(default)]
+    pub auto_prune: bool,
+}
+
+/// Every stack's policy. Stacks without one keep their history forever.
+type Policies = BTreeMap<String, Retention>;
+
+/// The retention policy for a stack's history.
+pub(super) fn policy(backend: &dyn Backend, stack: &str) -> Result<Retention, SigiError> {
+    Ok(load(backend)?.remove(stack).unwrap_or_default())
+}
+
+/// Replace the retention policy for a stack's history.
+pub(super) fn set_policy(
+    backend: &dyn Backend,
+    stack: &str,
+    retention: Retention,
+) -> Result<(), SigiError> {
+    let mut policies = load(backend)?;
+    if retention == Retention::default() {
+        policies.remove(stack);
+    } else {
+        policies.insert(stack.to_string(), retention);
+    }
+    let json = serde_json::to_string(&policies)
+        .map_err(|err| SigiError::corrupt_data(RETENTION_KEY, err))?;
+    backend.save_meta(RETENTION_KEY, &json)
+}
+
+/// Split a history stack into the items to keep and the items to prune.
+/// Both keep their order, oldest first.
+pub(super) fn split(history: Stack, retention: &Retention, now: DateTime<Local>) -> (Stack, Stack) {
+    let oldest_allowed = retention
+        .max_age_days
+        .map(|days| now - Duration::days(days.into()));
+    let n_over = retention
+        .max_count
+        .map_or(0, |max| history.len().saturating_sub(max));
+
+    let (pruned, kept): (Vec<_>, Vec<_>) =
+        history.into_iter().enumerate().partition(|(i, item)| {
+            let too_old = match (oldest_allowed, archived_at(item)) {
+                (Some(oldest_allowed), Some(at)) => at < oldest_allowed,
+                _ => false,
+            };
+            *i < n_over || too_old
+        });
+
+    let strip = |items: Vec<(usize, Item)>| items.into_iter().map(|(_, item)| item).collect();
+    (strip(kept), strip(pruned))
+}
+
+/// When an item went into the history: the time of its latest event.
+fn archived_at(item: &Item) -> Option<DateTime<Local>> {
+    item.history.iter().map(|event| event.at).max()
+}
+
+fn load(backend: &dyn Backend) -> Result<Policies, SigiError> {
+    match backend.load_meta(RETENTION_KEY)? {
+        Some(json) => {
+            serde_json::from_str(&json).map_err(|err| SigiError::corrupt_data(RETENTION_KEY, err))
+        }
+        None => Ok(Policies::default()),
+    }
+}
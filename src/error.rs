@@ -0,0 +1,103 @@
+use std::{error, fmt, io};
+
+/// Everything that can go wrong while running a stack effect.
+///
+/// Each kind of error has its own exit code for the CLI. (See [`SigiError::exit_code`])
+#[derive(Debug)]
+pub enum SigiError {
+    /// Reading or writing the store failed, e.g. because of file permissions.
+    Io(io::Error),
+    /// A stack's saved data couldn't be understood.
+    CorruptData { stack: String, reason: String },
+    /// An effect needed a stack that doesn't exist.
+    UnknownStack(String),
+    /// Another process held the store's lock for too long.
+    LockTimeout,
+    /// The backend itself reported an error, e.g. a database or network error.
+    Backend(String),
+}
+
+impl SigiError {
+    /// A [`SigiError::CorruptData`] for a stack.
+    pub fn corrupt_data(stack: &str, reason: impl fmt::Display) -> Self {
+        SigiError::CorruptData {
+            stack: stack.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    /// The CLI's exit code for this error:
+    ///
+    /// | Code | Meaning                                            |
+    /// |------|----------------------------------------------------|
+    /// | 1    | (Not an error) `is-empty` found items in the stack |
+    /// | 2    | Invalid command-line arguments                     |
+    /// | 3    | [`SigiError::Io`]                                  |
+    /// | 4    | [`SigiError::CorruptData`]                         |
+    /// | 5    | [`SigiError::UnknownStack`]                        |
+    /// | 6    | [`SigiError::LockTimeout`]                         |
+    /// | 7    | [`SigiError::Backend`]                             |
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            SigiError::Io(_) => 3,
+            SigiError::CorruptData { .. } => 4,
+            SigiError::UnknownStack(_) => 5,
+            SigiError::LockTimeout => 6,
+            SigiError::Backend(_) => 7,
+        }
+    }
+
+    /// A short, stable name for this kind of error, for programmatic output.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SigiError::Io(_) => "io",
+            SigiError::CorruptData { .. } => "corrupt-data",
+            SigiError::UnknownStack(_) => "unknown-stack",
+            SigiError::LockTimeout => "lock-timeout",
+            SigiError::Backend(_) => "backend",
+        }
+    }
+}
+
+impl error::Error for SigiError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            SigiError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SigiError {
+    fn fmt(&self, out: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            SigiError::Io(err) => write!(out, "{}", err),
+            SigiError::CorruptData { stack, reason } => {
+                write!(out, "Unreadable data in stack {:?}: {}", stack, reason)
+            }
+            SigiError::UnknownStack(stack) => write!(out, "Unknown stack: {}", stack),
+            SigiError::LockTimeout => write!(out, "Timed out waiting for another sigi to finish"),
+            SigiError::Backend(msg) => write!(out, "{}", msg),
+        }
+    }
+}
+
+impl From<io::Error> for SigiError {
+    fn from(err: io::Error) -> Self {
+        SigiError::Io(err)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for SigiError {
+    fn from(err: rusqlite::Error) -> Self {
+        SigiError::Backend(format!("SQLite error: {}", err))
+    }
+}
+
+#[cfg(feature = "redis")]
+impl From<redis::RedisError> for SigiError {
+    fn from(err: redis::RedisError) -> Self {
+        SigiError::Backend(format!("Redis error: {}", err))
+    }
+}
@@ -4,10 +4,13 @@
 //!
 //! - https://github.com/sigi-cli/sigi
 //!
-//! Its "database" is currently little more than json files, and handles only
+//! Its default "database" is little more than json files, and handles only
 //! String values. It can work for research or small loads, but would be
-//! sluggish for anything that needs to care about performance. Other backends
-//! like Redis and SQLite are planned.
+//! sluggish for anything that needs to care about performance. A SQLite
+//! backend is available for larger stacks, and a Redis backend for stacks
+//! shared between machines.
+//! Storage is pluggable: anything that implements [`data::Backend`] can be
+//! passed to [`effects::StackEffect::run`].
 //!
 //! Other internals are documented, but the project is early in development
 //! and should be considered **unstable** at best.
@@ -23,5 +26,8 @@ pub mod cli;
 /// The item, stack, and persistence implementation.
 pub mod data;
 
+/// The error type shared by effects and backends.
+pub mod error;
+
 /// The printing implementation.
 pub mod output;
@@ -1,28 +1,29 @@
-use chrono::{DateTime, Local};
+//! The general idea in this module is to take a table-ish output and render it in common formats.
+//!
+//! ```text
+//! labels: [a, b, c]
+//! values:[[1, 2, 3],
+//!         [4, 5, 6]]
+//! ```
+//!
+//! For example, as json:
+//! ```json
+//! [
+//!     {
+//!         "a": "1",
+//!         "b": "2",
+//!         "c": "3"
+//!     },
+//!     {
+//!         "a": "4",
+//!         "b": "5",
+//!         "c": "6"
+//!     }
+//! ]
+//! ```
 
-/// The general idea in this module is to take a table-ish output and render it in common formats.
-///
-/// ```text
-/// labels: [a, b, c]
-/// values:[[1, 2, 3],
-///         [4, 5, 6]]
-/// ```
-///
-/// For example, as json:
-/// ```json
-/// [
-///     {
-///         "a": "1",
-///         "b": "2",
-///         "c": "3"
-///     },
-///     {
-///         "a": "4",
-///         "b": "5",
-///         "c": "6"
-///     }
-/// ]
-/// ```
+use crate::error::SigiError;
+use chrono::{DateTime, Local};
 
 /// Output formats supported by Sigi.
 #[derive(Clone, Copy, Eq, PartialEq)]
@@ -94,20 +95,27 @@ impl OutputFormat {
             }
             OutputFormat::Human(noise) => match noise {
                 NoiseLevel::Verbose => {
-                    values.into_iter().for_each(|line| match line.len() {
-                        0 => (),
-                        1 => println!("{}", line.first().unwrap()),
-                        2 => println!("{}: {}", line.first().unwrap(), line.get(1).unwrap()),
-                        _ => println!(
-                            "{}: {} ({})",
-                            line.first().unwrap(),
-                            line.get(1).unwrap(),
-                            line.iter()
-                                .skip(2)
-                                .map(|s| s.to_string())
-                                .collect::<Vec<_>>()
-                                .join(", ")
-                        ),
+                    values.into_iter().for_each(|line| {
+                        // Leave out empty extras, like an item without tags.
+                        let extras = line
+                            .iter()
+                            .skip(2)
+                            .filter(|s| !s.is_empty())
+                            .map(|s| s.to_string())
+                            .collect::<Vec<_>>();
+                        match (line.len(), extras.is_empty()) {
+                            (0, _) => (),
+                            (1, _) => println!("{}", line.first().unwrap()),
+                            (_, true) => {
+                                println!("{}: {}", line.first().unwrap(), line.get(1).unwrap())
+                            }
+                            (_, false) => println!(
+                                "{}: {} ({})",
+                                line.first().unwrap(),
+                                line.get(1).unwrap(),
+                                extras.join(", ")
+                            ),
+                        }
                     });
                 }
                 NoiseLevel::Normal => {
@@ -123,29 +131,11 @@ impl OutputFormat {
                 NoiseLevel::Quiet => quiet_print(values),
             },
             OutputFormat::Json => {
-                let keys = labels;
-                let objs = values
-                    .into_iter()
-                    .map(|vals| {
-                        let mut obj = json::JsonValue::new_object();
-                        keys.iter().zip(vals).for_each(|(k, v)| obj[*k] = v.into());
-                        obj
-                    })
-                    .collect::<Vec<_>>();
-
+                let objs = json_objects(labels, values);
                 println!("{}", json::stringify_pretty(objs, 2));
             }
             OutputFormat::JsonCompact => {
-                let keys = labels;
-                let objs = values
-                    .into_iter()
-                    .map(|vals| {
-                        let mut obj = json::JsonValue::new_object();
-                        keys.iter().zip(vals).for_each(|(k, v)| obj[*k] = v.into());
-                        obj
-                    })
-                    .collect::<Vec<_>>();
-
+                let objs = json_objects(labels, values);
                 println!("{}", json::stringify(objs));
             }
             OutputFormat::Silent => {
@@ -163,6 +153,52 @@ impl OutputFormat {
     }
 }
 
+impl OutputFormat {
+    /// Print an error to standard error, in the same format as other output.
+    /// Nothing is printed when silent.
+    pub fn log_error(&self, err: &SigiError) {
+        let labels = vec!["error", "message"];
+        let message = err.to_string();
+        let values = vec![vec![err.kind(), message.as_str()]];
+
+        match self {
+            OutputFormat::Silent => (),
+            OutputFormat::Human(_) | OutputFormat::TerseText => eprintln!("Error: {}", message),
+            OutputFormat::Csv => {
+                eprintln!("{}", labels.join(","));
+                values
+                    .iter()
+                    .for_each(|line| eprintln!("{}", line.join(",")));
+            }
+            OutputFormat::Tsv => {
+                eprintln!("{}", labels.join("\t"));
+                values
+                    .iter()
+                    .for_each(|line| eprintln!("{}", line.join("\t")));
+            }
+            OutputFormat::Json => {
+                let objs = json_objects(labels, values);
+                eprintln!("{}", json::stringify_pretty(objs, 2));
+            }
+            OutputFormat::JsonCompact => {
+                let objs = json_objects(labels, values);
+                eprintln!("{}", json::stringify(objs));
+            }
+        }
+    }
+}
+
+fn json_objects(keys: Vec<&str>, values: Vec<Vec<&str>>) -> Vec<json::JsonValue> {
+    values
+        .into_iter()
+        .map(|vals| {
+            let mut obj = json::JsonValue::new_object();
+            keys.iter().zip(vals).for_each(|(k, v)| obj[*k] = v.into());
+            obj
+        })
+        .collect()
+}
+
 fn quiet_print(values: Vec<Vec<&str>>) {
     values.into_iter().for_each(|line| {
         // Print only second value (item) separated by a single space.
@@ -0,0 +1,154 @@
+mod run_sigi;
+
+use run_sigi::{fresh_dir, json_rows, piping, sigi};
+
+#[test]
+fn sigi_amend_current_item() {
+    let stack = "_integ::amend";
+    let dir = fresh_dir("amend");
+
+    sigi(stack, &["--data-dir", &dir, "push", "Call mom"]).assert_success();
+    let res = sigi(stack, &["--data-dir", &dir, "-f", "json", "list"]);
+    let before = json_rows(res.stdout());
+
+    let res = sigi(
+        stack,
+        &["--data-dir", &dir, "amend", "Call", "dad", "+family"],
+    );
+    res.assert_success();
+    res.assert_stdout_eq("Edited: Call dad +family\n");
+
+    // Same item, new text and tags.
+    let res = sigi(stack, &["--data-dir", &dir, "-f", "json", "list"]);
+    let after = json_rows(res.stdout());
+    assert_eq!(after.len(), 1);
+    assert_eq!(after[0]["item"], "Call dad +family");
+    assert_eq!(after[0]["tags"], "family");
+    assert_eq!(after[0]["id"], before[0]["id"]);
+    assert_eq!(after[0]["created"], before[0]["created"]);
+
+    // The edit is in the history with the previous text, and nothing was deleted.
+    let res = sigi(stack, &["--data-dir", &dir, "-f", "json", "history"]);
+    let events = json_rows(res.stdout());
+    let names: Vec<&str> = events
+        .iter()
+        .map(|event| event["event"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["edited", "created"]);
+    assert_eq!(events[0]["detail"], "Call mom");
+    assert_eq!(events[1]["detail"], "");
+}
+
+#[test]
+fn sigi_amend_by_position_and_id() {
+    let stack = "_integ::amend_position";
+    let dir = fresh_dir("amend_position");
+
+    for item in ["Buy mlik", "Call mom"] {
+        sigi(stack, &["--data-dir", &dir, "push", item]).assert_success();
+    }
+
+    let res = sigi(stack, &["--data-dir", &dir, "amend", "1", "Buy milk"]);
+    res.assert_success();
+    res.assert_stdout_eq("Edited: Buy milk\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "-f", "json", "peek"]);
+    let id = json_rows(res.stdout())[0]["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    sigi(stack, &["--data-dir", &dir, "swap"]).assert_success();
+    sigi(stack, &["--data-dir", &dir, "amend", &id, "Call dad"]).assert_success();
+
+    // A lone number is the new text.
+    sigi(stack, &["--data-dir", &dir, "amend", "42"]).assert_success();
+
+    let res = sigi(stack, &["--data-dir", &dir, "list"]);
+    res.assert_stdout_eq("Now: 42\n  1: Call dad\n");
+}
+
+#[test]
+fn sigi_amend_substitute() {
+    let stack = "_integ::amend_substitute";
+    let dir = fresh_dir("amend_substitute");
+
+    sigi(stack, &["--data-dir", &dir, "push", "teh cat and teh hat"]).assert_success();
+
+    let res = sigi(stack, &["--data-dir", &dir, "amend", "s/teh/the/"]);
+    res.assert_stdout_eq("Edited: the cat and teh hat\n");
+    let res = sigi(stack, &["--data-dir", &dir, "amend", "s/teh/the/g"]);
+    res.assert_stdout_eq("Edited: the cat and the hat\n");
+
+    // Nothing to replace, so nothing changes.
+    let res = sigi(stack, &["--data-dir", &dir, "amend", "s/dog/cow/"]);
+    res.assert_success();
+    res.assert_stdout_eq("Edited: NOTHING\n");
+
+    let res = sigi(
+        stack,
+        &[
+            "--data-dir",
+            &dir,
+            "-f",
+            "json",
+            "history",
+            "--event",
+            "edited",
+        ],
+    );
+    let events = json_rows(res.stdout());
+    let previous: Vec<&str> = events
+        .iter()
+        .map(|event| event["detail"].as_str().unwrap())
+        .collect();
+    assert_eq!(previous, ["the cat and teh hat", "teh cat and teh hat"]);
+}
+
+#[test]
+fn sigi_amend_nothing() {
+    let stack = "_integ::amend_nothing";
+    let dir = fresh_dir("amend_nothing");
+
+    let res = sigi(stack, &["--data-dir", &dir, "amend", "Hello"]);
+    res.assert_success();
+    res.assert_stdout_eq("Edited: NOTHING\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "amend"]);
+    res.assert_exit_code(2);
+}
+
+#[test]
+fn sigi_amend_interactive() {
+    let stack = "_integ::amend_interactive";
+    let dir = fresh_dir("amend_interactive");
+
+    let res = piping(&[
+        "push Buy mlik",
+        "push Call mom",
+        "amend 1 s/mlik/milk/",
+        "amend Call  dad",
+        "list",
+    ])
+    .into_sigi(stack, &["--data-dir", &dir, "-"]);
+    res.assert_stderr_empty();
+    res.assert_stdout_eq("Call  dad\nBuy milk\n");
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sigi_amend_on_sqlite() {
+    let stack = "_integ::amend_sqlite";
+    let dir = fresh_dir("amend_sqlite");
+    let args = run_sigi::backend_args(&dir, "sqlite");
+
+    sigi(stack, &[&args[..], &["push", "Call mom"]].concat()).assert_success();
+    sigi(stack, &[&args[..], &["amend", "s/mom/dad/"]].concat()).assert_success();
+
+    let res = sigi(
+        stack,
+        &[&args[..], &["-f", "json", "history", "--event", "edited"]].concat(),
+    );
+    let events = json_rows(res.stdout());
+    assert_eq!(events[0]["item"], "Call dad");
+    assert_eq!(events[0]["detail"], "Call mom");
+}
@@ -0,0 +1,131 @@
+use sigi::data::{Backend, Item, Stack};
+use sigi::effects::{EffectResult, StackEffect, View};
+use sigi::error::SigiError;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// A store that lives only in memory, to show that effects work with any `Backend`.
+#[derive(Default)]
+struct InMemory {
+    stacks: RefCell<HashMap<String, Stack>>,
+}
+
+impl Backend for InMemory {
+    fn load(&self, stack_name: &str) -> Result<Stack, SigiError> {
+        Ok(self
+            .stacks
+            .borrow()
+            .get(stack_name)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn save(&self, stack_name: &str, items: Stack) -> Result<(), SigiError> {
+        self.stacks
+            .borrow_mut()
+            .insert(stack_name.to_string(), items);
+        Ok(())
+    }
+
+    fn list_stacks(&self) -> Result<Vec<String>, SigiError> {
+        Ok(self.stacks.borrow().keys().cloned().collect())
+    }
+}
+
+fn contents(backend: &dyn Backend, stack: &str) -> Vec<String> {
+    backend
+        .load(stack)
+        .unwrap()
+        .into_iter()
+        .map(|item: Item| item.contents)
+        .collect()
+}
+
+#[test]
+fn effects_run_against_custom_backend() {
+    let backend = InMemory::default();
+    let stack = || "custom".to_string();
+
+    for content in ["a", "b", "c"] {
+        let push = StackEffect::Push {
+            stack: stack(),
+            item: Item::new(content),
+        };
+        push.run(&backend).unwrap();
+    }
+    assert_eq!(contents(&backend, "custom"), ["a", "b", "c"]);
+
+    StackEffect::Complete { stack: stack() }
+        .run(&backend)
+        .unwrap();
+    assert_eq!(contents(&backend, "custom"), ["a", "b"]);
+    assert_eq!(contents(&backend, "custom_history"), ["c"]);
+
+    let move_all = StackEffect::MoveAll {
+        stack: stack(),
+        dest: "elsewhere".to_string(),
+    };
+    move_all.run(&backend).unwrap();
+    assert!(contents(&backend, "custom").is_empty());
+    assert_eq!(contents(&backend, "elsewhere"), ["a", "b"]);
+
+    let mut stacks = backend.list_stacks().unwrap();
+    stacks.sort();
+    assert_eq!(stacks, ["custom", "custom_history", "elsewhere"]);
+}
+
+#[test]
+fn effects_return_results() {
+    let backend = InMemory::default();
+    let stack = || "results".to_string();
+
+    for content in ["a", "b", "c"] {
+        let push = StackEffect::Push {
+            stack: stack(),
+            item: Item::new(content),
+        };
+        match push.run(&backend).unwrap() {
+            EffectResult::Pushed { item, .. } => assert_eq!(item.contents, content),
+            _ => panic!("push should report the pushed item"),
+        }
+    }
+
+    let complete = StackEffect::Complete { stack: stack() };
+    match complete.run(&backend).unwrap() {
+        EffectResult::Completed { item, now, .. } => {
+            assert_eq!(item.unwrap().contents, "c");
+            assert_eq!(now.unwrap().contents, "b");
+        }
+        _ => panic!("complete should report the completed item"),
+    }
+
+    let tail = StackEffect::Tail {
+        stack: stack(),
+        n: 1,
+        view: View::default(),
+    };
+    match tail.run(&backend).unwrap() {
+        EffectResult::Listed { items, .. } => {
+            assert_eq!(items.len(), 1);
+            let (position, item) = &items[0];
+            assert_eq!(*position, 1);
+            assert_eq!(item.contents, "a");
+        }
+        _ => panic!("tail should list items"),
+    }
+
+    let count = StackEffect::Count {
+        stack: stack(),
+        tags: vec![],
+    };
+    match count.run(&backend).unwrap() {
+        EffectResult::Counted { count, .. } => assert_eq!(count, 2),
+        _ => panic!("count should report a count"),
+    }
+
+    let is_empty = StackEffect::IsEmpty { stack: stack() };
+    match is_empty.run(&backend).unwrap() {
+        EffectResult::IsEmpty { empty, .. } => assert!(!empty),
+        _ => panic!("is-empty should report a boolean"),
+    }
+}
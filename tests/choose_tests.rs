@@ -0,0 +1,243 @@
+#![cfg(feature = "tui")]
+
+mod run_sigi;
+
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::Terminal;
+use run_sigi::{fresh_dir, sigi};
+use sigi::cli::choose::{Choose, ChooseAction};
+use sigi::data::HomeDir;
+use std::path::Path;
+
+fn setup(stack: &str, args: &[&str]) {
+    for item in ["Buy milk", "Call mom", "Write docs"] {
+        sigi(stack, &[args, &["push", item]].concat()).assert_success();
+    }
+}
+
+// The fuzzy finder itself needs a terminal, so these choose with --select-1.
+
+#[test]
+fn sigi_choose_pick() {
+    let stack = "_integ::choose";
+    let dir = fresh_dir("choose");
+    setup(stack, &["--data-dir", &dir]);
+
+    let res = sigi(
+        stack,
+        &["--data-dir", &dir, "choose", "-1", "--query", "mlk"],
+    );
+    res.assert_success();
+    res.assert_stdout_eq("Now: Buy milk\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "list"]);
+    res.assert_stdout_eq("Now: Buy milk\n  1: Write docs\n  2: Call mom\n");
+}
+
+#[test]
+fn sigi_choose_then_act() {
+    let stack = "_integ::choose_act";
+    let dest = "_integ::choose_act_dest";
+    let dir = fresh_dir("choose_act");
+    setup(stack, &["--data-dir", &dir]);
+
+    let res = sigi(
+        stack,
+        &[
+            "--data-dir",
+            &dir,
+            "choose",
+            "complete",
+            "-1",
+            "--query",
+            "mom",
+        ],
+    );
+    res.assert_stdout_eq("Completed: Call mom\nNow: Write docs\n");
+
+    let res = sigi(
+        stack,
+        &[
+            "--data-dir",
+            &dir,
+            "choose",
+            "move",
+            dest,
+            "-1",
+            "--query",
+            "milk",
+        ],
+    );
+    res.assert_success();
+
+    let res = sigi(
+        stack,
+        &[
+            "--data-dir",
+            &dir,
+            "choose",
+            "delete",
+            "-1",
+            "--query",
+            "docs",
+        ],
+    );
+    res.assert_stdout_eq("Deleted: Write docs\nNow: NOTHING\n");
+
+    let res = sigi(dest, &["--data-dir", &dir, "list"]);
+    res.assert_stdout_eq("Now: Buy milk\n");
+
+    let res = sigi(
+        stack,
+        &[
+            "--data-dir",
+            &dir,
+            "-q",
+            "history",
+            "--event",
+            "completed,deleted",
+        ],
+    );
+    res.assert_stdout_eq("Write docs\nCall mom\n");
+}
+
+#[test]
+fn sigi_choose_then_undo() {
+    let stack = "_integ::choose_undo";
+    let dir = fresh_dir("choose_undo");
+    setup(stack, &["--data-dir", &dir]);
+
+    let args = [
+        "--data-dir",
+        &dir,
+        "choose",
+        "complete",
+        "-1",
+        "--query",
+        "mom",
+    ];
+    sigi(stack, &args).assert_success();
+
+    // Picking and completing are undone together.
+    let res = sigi(stack, &["--data-dir", &dir, "undo"]);
+    res.assert_stdout_eq("Undone: complete\n");
+    let res = sigi(stack, &["--data-dir", &dir, "-q", "list"]);
+    res.assert_stdout_eq("Write docs\nCall mom\nBuy milk\n");
+}
+
+#[test]
+fn sigi_choose_from_all_stacks() {
+    let stack = "_integ::choose_all";
+    let other = "_integ::choose_all_other";
+    let dir = fresh_dir("choose_all");
+    setup(stack, &["--data-dir", &dir]);
+    for item in ["Merge docs", "Review PR"] {
+        sigi(other, &["--data-dir", &dir, "push", item]).assert_success();
+    }
+
+    let res = sigi(
+        stack,
+        &[
+            "--data-dir",
+            &dir,
+            "choose",
+            "complete",
+            "--all",
+            "-1",
+            "--query",
+            "review",
+        ],
+    );
+    res.assert_stdout_eq("Completed: Review PR\nNow: Merge docs\n");
+
+    // The stack name can be matched too.
+    let res = sigi(
+        stack,
+        &[
+            "--data-dir",
+            &dir,
+            "choose",
+            "delete",
+            "--all",
+            "-1",
+            "--query",
+            "other docs",
+        ],
+    );
+    res.assert_stdout_eq("Deleted: Merge docs\nNow: NOTHING\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "-q", "list"]);
+    res.assert_stdout_eq("Write docs\nCall mom\nBuy milk\n");
+}
+
+#[test]
+fn sigi_choose_nothing() {
+    let stack = "_integ::choose_nothing";
+    let dir = fresh_dir("choose_nothing");
+
+    let res = sigi(stack, &["--data-dir", &dir, "choose"]);
+    res.assert_success();
+    res.assert_stdout_eq("Chosen: NOTHING\n");
+
+    setup(stack, &["--data-dir", &dir]);
+    let res = sigi(
+        stack,
+        &["--data-dir", &dir, "choose", "-1", "--query", "zzz"],
+    );
+    res.assert_stdout_eq("Chosen: NOTHING\n");
+}
+
+#[test]
+fn sigi_choose_needs_terminal() {
+    let stack = "_integ::choose_terminal";
+    let dir = fresh_dir("choose_terminal");
+    setup(stack, &["--data-dir", &dir]);
+
+    // Two items match, so it would have to ask.
+    let res = sigi(stack, &["--data-dir", &dir, "choose", "-1", "--query", "l"]);
+    res.assert_exit_code(3);
+    let res = sigi(stack, &["--data-dir", &dir, "pick", "--fuzzy"]);
+    res.assert_exit_code(3);
+
+    let res = sigi(stack, &["--data-dir", &dir, "pick", "--fuzzy", "1"]);
+    res.assert_exit_code(2);
+    let res = sigi(stack, &["--data-dir", &dir, "choose", "move"]);
+    res.assert_exit_code(2);
+
+    let res = sigi(stack, &["--data-dir", &dir, "-q", "list"]);
+    res.assert_stdout_eq("Write docs\nCall mom\nBuy milk\n");
+}
+
+#[test]
+fn choose_several_with_a_recurring_item() {
+    let stack = "_integ::choose_recurring";
+    let dir = fresh_dir("choose_recurring");
+    sigi(stack, &["--data-dir", &dir, "push", "Other"]).assert_success();
+    let args = ["--data-dir", &dir, "push", "--repeat", "daily", "Chore"];
+    sigi(stack, &args).assert_success();
+
+    // Mark both items in the fuzzy finder and complete them.
+    let choose = Choose {
+        stack: stack.to_string(),
+        all_stacks: false,
+        query: String::new(),
+        select_one: false,
+        action: ChooseAction::Complete,
+        dest: None,
+    };
+    let backend = HomeDir::at(Path::new(&dir));
+    let mut terminal = Terminal::new(TestBackend::new(80, 10)).unwrap();
+    let keys = [KeyCode::Tab, KeyCode::Tab, KeyCode::Enter].map(KeyEvent::from);
+    let results = choose.run_with_keys(&backend, &mut terminal, keys).unwrap();
+    assert_eq!(results.len(), 2);
+
+    // Completing the chore brings its next occurrence back, which stays.
+    let res = sigi(stack, &["--data-dir", &dir, "-q", "list"]);
+    res.assert_stdout_eq("Chore\n");
+    let res = sigi(
+        stack,
+        &["--data-dir", &dir, "-q", "history", "--event", "completed"],
+    );
+    res.assert_stdout_eq("Other\nChore\n");
+}
@@ -0,0 +1,57 @@
+mod run_sigi;
+
+use run_sigi::{backend_args, fresh_dir, sigi, SIGI_PATH};
+use std::fs;
+use std::process::Command;
+
+const PUSHES: usize = 40;
+
+fn concurrent_pushes(backend: &str) {
+    let stack = "_integ::concurrent";
+    let dir = fresh_dir(&format!("concurrent_{}", backend));
+    let args = backend_args(&dir, backend);
+
+    let processes = (0..PUSHES)
+        .map(|i| {
+            Command::new(SIGI_PATH)
+                .args(args)
+                .args(["--stack", stack])
+                .args(["--silent", "push", &format!("item {}", i)])
+                .spawn()
+                .expect("Error running process")
+        })
+        .collect::<Vec<_>>();
+
+    for mut process in processes {
+        assert!(process.wait().unwrap().success());
+    }
+
+    let res = sigi(stack, &[&args[..], &["list", "-q"]].concat());
+    res.assert_success();
+    res.assert_stderr_empty();
+    for i in 0..PUSHES {
+        res.assert_stdout_line_eq(&format!("item {}", i));
+    }
+
+    let res = sigi(stack, &[&args[..], &["count"]].concat());
+    res.assert_stdout_eq(&format!("{}\n", PUSHES));
+
+    // Nothing is left behind from writing files atomically.
+    let leftovers = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .filter(|name| name.ends_with(".tmp"))
+        .collect::<Vec<_>>();
+    assert!(leftovers.is_empty(), "Leftover files: {:?}", leftovers);
+}
+
+#[test]
+fn sigi_concurrent_pushes_json() {
+    concurrent_pushes("json");
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sigi_concurrent_pushes_sqlite() {
+    concurrent_pushes("sqlite");
+}
@@ -0,0 +1,43 @@
+mod run_sigi;
+
+use run_sigi::{fresh_dir, piping, sigi};
+use std::fs;
+
+#[test]
+fn sigi_data_dir_flag() {
+    let stack = "_integ::data_dir";
+    let dir = fresh_dir("data_dir_flag");
+
+    let res = sigi(stack, &["--data-dir", &dir, "push", "isolated"]);
+    res.assert_success();
+    res.assert_stdout_eq("Created: isolated\n");
+    res.assert_stderr_empty();
+
+    assert!(fs::metadata(format!("{}/{}.json", dir, stack)).is_ok());
+
+    // The flag is global, so it's also understood after the subcommand.
+    let res = sigi(stack, &["peek", "--data-dir", &dir]);
+    res.assert_success();
+    res.assert_stdout_eq("Now: isolated\n");
+    res.assert_stderr_empty();
+
+    // The default test directory doesn't see it.
+    let res = sigi(stack, &["peek"]);
+    res.assert_success();
+    res.assert_stdout_eq("Now: NOTHING\n");
+    res.assert_stderr_empty();
+}
+
+#[test]
+fn sigi_data_dir_interactive() {
+    let stack = "_integ::data_dir_interactive";
+    let dir = fresh_dir("data_dir_interactive");
+
+    let res = piping(&["push from stdin"]).into_sigi(stack, &["--data-dir", &dir, "-"]);
+    res.assert_stderr_empty();
+
+    let res = sigi(stack, &["--data-dir", &dir, "list"]);
+    res.assert_success();
+    res.assert_stdout_eq("Now: from stdin\n");
+    res.assert_stderr_empty();
+}
@@ -0,0 +1,159 @@
+mod run_sigi;
+
+use chrono::{Datelike, Days, Local, TimeZone, Weekday};
+use run_sigi::{fresh_dir, json_rows, sigi};
+
+/// How a due date at the end of a day `days` from now is printed.
+fn end_of_day_in(days: u64) -> String {
+    let day = Local::now().date_naive() + Days::new(days);
+    let end = day.and_hms_nano_opt(23, 59, 59, 999_999_999).unwrap();
+    Local.from_local_datetime(&end).unwrap().to_rfc2822()
+}
+
+#[test]
+fn sigi_push_with_due_date() {
+    let stack = "_integ::due";
+    let dir = fresh_dir("due");
+
+    let res = sigi(
+        stack,
+        &[
+            "--data-dir",
+            &dir,
+            "push",
+            "--due",
+            "2020-01-01",
+            "Pay rent",
+        ],
+    );
+    res.assert_success();
+    res.assert_stdout_eq("Created: Pay rent\n");
+    sigi(
+        stack,
+        &["--data-dir", &dir, "push", "--due", "tomorrow", "Buy milk"],
+    )
+    .assert_success();
+    sigi(stack, &["--data-dir", &dir, "push", "Whenever"]).assert_success();
+
+    // Overdue items are flagged for humans.
+    let res = sigi(stack, &["--data-dir", &dir, "list"]);
+    res.assert_stdout_eq("Now: Whenever\n  1: Buy milk\n  2: Pay rent (OVERDUE)\n");
+
+    // ...and have a boolean field otherwise.
+    let res = sigi(stack, &["--data-dir", &dir, "-f", "json", "list"]);
+    let rows = json_rows(res.stdout());
+    assert_eq!(rows[0]["due"], "");
+    assert_eq!(rows[0]["overdue"], "false");
+    assert_eq!(rows[1]["item"], "Buy milk");
+    assert_eq!(rows[1]["due"], end_of_day_in(1).as_str());
+    assert_eq!(rows[1]["overdue"], "false");
+    assert_eq!(rows[2]["item"], "Pay rent");
+    assert_eq!(rows[2]["overdue"], "true");
+
+    let res = sigi(
+        stack,
+        &["--data-dir", &dir, "push", "--due", "someday", "x"],
+    );
+    res.assert_exit_code(2);
+}
+
+#[test]
+fn sigi_due_weekday() {
+    let stack = "_integ::due_weekday";
+    let dir = fresh_dir("due_weekday");
+
+    sigi(stack, &["--data-dir", &dir, "push", "--due", "fri", "Demo"]).assert_success();
+
+    let today = Local::now().weekday();
+    let days_ahead = Weekday::Fri.days_since(today);
+    let res = sigi(stack, &["--data-dir", &dir, "-f", "json", "peek"]);
+    let rows = json_rows(res.stdout());
+    assert_eq!(rows[0]["due"], end_of_day_in(days_ahead.into()).as_str());
+}
+
+#[test]
+fn sigi_due_across_stacks() {
+    let dir = fresh_dir("due_across");
+
+    for (stack, due, item) in [
+        ("_integ::due_home", "2026-12-01", "Decorate"),
+        ("_integ::due_work", "2020-01-01", "File expenses"),
+        ("_integ::due_home", "", "Someday"),
+        ("_integ::due_work", "2099-01-01", "Retire"),
+        ("_integ::due_home", "2021-06-01", "Call mom"),
+    ] {
+        let args = if due.is_empty() {
+            vec!["--data-dir", &dir, "push", item]
+        } else {
+            vec!["--data-dir", &dir, "push", "--due", due, item]
+        };
+        sigi(stack, &args).assert_success();
+    }
+    // Finished items aren't due anymore.
+    sigi(
+        "_integ::due_home",
+        &[
+            "--data-dir",
+            &dir,
+            "push",
+            "--due",
+            "2020-01-01",
+            "Done already",
+        ],
+    )
+    .assert_success();
+    sigi("_integ::due_home", &["--data-dir", &dir, "complete"]).assert_success();
+
+    let res = sigi("_integ::due_home", &["--data-dir", &dir, "-q", "due"]);
+    res.assert_success();
+    res.assert_stdout_eq("File expenses\nCall mom\nDecorate\nRetire\n");
+
+    let res = sigi("_integ::due_home", &["--data-dir", &dir, "overdue"]);
+    let lines: Vec<&str> = res.stdout().lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("Wed, 1 Jan 2020 23:59:59 "));
+    assert!(lines[0].ends_with(": File expenses (OVERDUE)"));
+    assert!(lines[1].starts_with("Tue, 1 Jun 2021 23:59:59 "));
+    assert!(lines[1].ends_with(": Call mom (OVERDUE)"));
+
+    let res = sigi(
+        "_integ::due_home",
+        &["--data-dir", &dir, "-f", "csv", "overdue"],
+    );
+    res.assert_stdout_lines_eq(&["due,item,stack,position,overdue,id"]);
+    let rows: Vec<&str> = res.stdout().lines().collect();
+    assert_eq!(rows.len(), 3);
+    assert!(rows[1].contains(",File expenses,_integ::due_work,1,true,"));
+    assert!(rows[2].contains(",Call mom,_integ::due_home,0,true,"));
+}
+
+#[test]
+fn sigi_due_nothing() {
+    let dir = fresh_dir("due_nothing");
+
+    sigi(
+        "_integ::due_nothing",
+        &["--data-dir", &dir, "push", "No rush"],
+    )
+    .assert_success();
+
+    let res = sigi("_integ::due_nothing", &["--data-dir", &dir, "due"]);
+    res.assert_success();
+    res.assert_stdout_eq("Due: NOTHING\n");
+    let res = sigi("_integ::due_nothing", &["--data-dir", &dir, "overdue"]);
+    res.assert_stdout_eq("Overdue: NOTHING\n");
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sigi_due_on_sqlite() {
+    let stack = "_integ::due_sqlite";
+    let dir = fresh_dir("due_sqlite");
+    let args = run_sigi::backend_args(&dir, "sqlite");
+
+    let push = [&args[..], &["push", "--due", "2020-01-01", "Pay rent"]].concat();
+    sigi(stack, &push).assert_success();
+
+    let res = sigi(stack, &[&args[..], &["-q", "overdue"]].concat());
+    res.assert_stdout_eq("Pay rent\n");
+}
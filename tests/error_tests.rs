@@ -0,0 +1,50 @@
+mod run_sigi;
+
+use run_sigi::{fresh_dir, sigi};
+use std::fs;
+
+#[test]
+fn sigi_corrupt_stack() {
+    let stack = "_integ::corrupt";
+    let dir = fresh_dir("corrupt");
+    fs::write(format!("{}/{}.json", dir, stack), "not json").unwrap();
+
+    let res = sigi(stack, &["--data-dir", &dir, "peek"]);
+    res.assert_failure();
+    res.assert_exit_code(4);
+    res.assert_stdout_eq("");
+    res.assert_stderr_eq(
+        "Error: Unreadable data in stack \"_integ::corrupt\": expected ident at line 1 column 2\n",
+    );
+
+    let res = sigi(
+        stack,
+        &["--data-dir", &dir, "--format=json-compact", "peek"],
+    );
+    res.assert_exit_code(4);
+    res.assert_stderr_eq(
+        "[{\"error\":\"corrupt-data\",\"message\":\"Unreadable data in stack \\\"_integ::corrupt\\\": expected ident at line 1 column 2\"}]\n",
+    );
+}
+
+#[test]
+fn sigi_move_from_unknown_stack() {
+    let stack = "_integ::unknown_source";
+    let dir = fresh_dir("unknown_source");
+
+    let res = sigi(stack, &["--data-dir", &dir, "move", "_integ::elsewhere"]);
+    res.assert_failure();
+    res.assert_exit_code(5);
+    res.assert_stderr_eq("Error: Unknown stack: _integ::unknown_source\n");
+}
+
+#[test]
+fn sigi_silent_errors() {
+    let stack = "_integ::unknown_silent";
+    let dir = fresh_dir("unknown_silent");
+
+    let res = sigi(stack, &["--data-dir", &dir, "--silent", "move-all", "x"]);
+    res.assert_exit_code(5);
+    res.assert_stdout_eq("");
+    res.assert_stderr_empty();
+}
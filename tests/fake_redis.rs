@@ -0,0 +1,220 @@
+//! A tiny stand-in for a Redis server. It speaks just enough of the Redis
+//! protocol (RESP) for sigi's Redis backend, and keeps everything in memory.
+
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+enum Value {
+    String(Vec<u8>),
+    List(VecDeque<Vec<u8>>),
+    Set(BTreeSet<Vec<u8>>),
+}
+
+type Db = Arc<Mutex<HashMap<Vec<u8>, Value>>>;
+
+enum Reply {
+    Ok,
+    Queued,
+    Int(i64),
+    Bulk(Option<Vec<u8>>),
+    Array(Vec<Reply>),
+    Error(String),
+}
+
+/// Start a server on a free local port and return its URL. The server lives
+/// until the test process exits.
+pub fn start() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Unable to bind fake redis");
+    let url = format!("redis://{}/", listener.local_addr().unwrap());
+    let db: Db = Arc::default();
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = stream.unwrap();
+            let db = db.clone();
+            thread::spawn(move || serve(stream, db));
+        }
+    });
+
+    url
+}
+
+fn serve(stream: TcpStream, db: Db) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream;
+    let mut transaction: Option<Vec<Vec<Vec<u8>>>> = None;
+
+    while let Some(command) = read_command(&mut reader) {
+        let name = String::from_utf8_lossy(&command[0]).to_ascii_uppercase();
+        let reply = match (name.as_str(), &mut transaction) {
+            ("MULTI", _) => {
+                transaction = Some(vec![]);
+                Reply::Ok
+            }
+            ("EXEC", Some(_)) => {
+                let queued = transaction.take().unwrap();
+                let mut db = db.lock().unwrap();
+                Reply::Array(queued.iter().map(|cmd| execute(cmd, &mut db)).collect())
+            }
+            (_, Some(queued)) => {
+                queued.push(command);
+                Reply::Queued
+            }
+            (_, None) => execute(&command, &mut db.lock().unwrap()),
+        };
+        let mut out = vec![];
+        write_reply(&reply, &mut out);
+        if writer.write_all(&out).is_err() {
+            return;
+        }
+    }
+}
+
+fn read_command(reader: &mut impl BufRead) -> Option<Vec<Vec<u8>>> {
+    let header = read_line(reader)?;
+    let n: usize = header.strip_prefix('*')?.parse().ok()?;
+    (0..n)
+        .map(|_| {
+            let len: usize = read_line(reader)?.strip_prefix('$')?.parse().ok()?;
+            let mut arg = vec![0; len + 2];
+            reader.read_exact(&mut arg).ok()?;
+            arg.truncate(len);
+            Some(arg)
+        })
+        .collect()
+}
+
+fn read_line(reader: &mut impl BufRead) -> Option<String> {
+    let mut line = String::new();
+    match reader.read_line(&mut line) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(line.trim_end().to_string()),
+    }
+}
+
+fn execute(command: &[Vec<u8>], db: &mut HashMap<Vec<u8>, Value>) -> Reply {
+    let name = String::from_utf8_lossy(&command[0]).to_ascii_uppercase();
+    let args = &command[1..];
+    match name.as_str() {
+        "PING" => Reply::Ok,
+        "DEL" => {
+            let removed = args.iter().filter(|key| db.remove(*key).is_some()).count();
+            Reply::Int(removed as i64)
+        }
+        "GET" => match db.get(&args[0]) {
+            Some(Value::String(value)) => Reply::Bulk(Some(value.clone())),
+            None => Reply::Bulk(None),
+            _ => wrong_type(),
+        },
+        "SET" => {
+            // Expiry options like PX are accepted but ignored.
+            let nx = args[2..].iter().any(|arg| arg.eq_ignore_ascii_case(b"NX"));
+            if nx && db.contains_key(&args[0]) {
+                return Reply::Bulk(None);
+            }
+            db.insert(args[0].clone(), Value::String(args[1].clone()));
+            Reply::Ok
+        }
+        // No Lua here: the only script sigi runs deletes KEYS[1] if it holds ARGV[1].
+        "EVAL" => match db.get(&args[2]) {
+            Some(Value::String(value)) if *value == args[3] => {
+                db.remove(&args[2]);
+                Reply::Int(1)
+            }
+            _ => Reply::Int(0),
+        },
+        "RPUSH" => match db
+            .entry(args[0].clone())
+            .or_insert_with(|| Value::List(VecDeque::new()))
+        {
+            Value::List(list) => {
+                list.extend(args[1..].iter().cloned());
+                Reply::Int(list.len() as i64)
+            }
+            _ => wrong_type(),
+        },
+        "RPOP" => match db.get_mut(&args[0]) {
+            Some(Value::List(list)) => {
+                let popped = list.pop_back();
+                if list.is_empty() {
+                    db.remove(&args[0]);
+                }
+                Reply::Bulk(popped)
+            }
+            None => Reply::Bulk(None),
+            _ => wrong_type(),
+        },
+        "LRANGE" => match db.get(&args[0]) {
+            Some(Value::List(list)) => {
+                let len = list.len() as i64;
+                let index = |arg: &[u8]| {
+                    let i: i64 = String::from_utf8_lossy(arg).parse().unwrap();
+                    if i < 0 {
+                        (len + i).max(0)
+                    } else {
+                        i.min(len)
+                    }
+                };
+                let (start, stop) = (index(&args[1]), index(&args[2]));
+                let items = list
+                    .iter()
+                    .skip(start as usize)
+                    .take((stop - start + 1).max(0) as usize)
+                    .map(|item| Reply::Bulk(Some(item.clone())))
+                    .collect();
+                Reply::Array(items)
+            }
+            None => Reply::Array(vec![]),
+            _ => wrong_type(),
+        },
+        "SADD" => match db
+            .entry(args[0].clone())
+            .or_insert_with(|| Value::Set(BTreeSet::new()))
+        {
+            Value::Set(set) => {
+                let added = args[1..]
+                    .iter()
+                    .filter(|member| set.insert(member.to_vec()))
+                    .count();
+                Reply::Int(added as i64)
+            }
+            _ => wrong_type(),
+        },
+        "SMEMBERS" => match db.get(&args[0]) {
+            Some(Value::Set(set)) => Reply::Array(
+                set.iter()
+                    .map(|member| Reply::Bulk(Some(member.clone())))
+                    .collect(),
+            ),
+            None => Reply::Array(vec![]),
+            _ => wrong_type(),
+        },
+        _ => Reply::Error(format!("ERR unknown command '{}'", name)),
+    }
+}
+
+fn wrong_type() -> Reply {
+    Reply::Error("WRONGTYPE Operation against a key holding the wrong kind of value".to_string())
+}
+
+fn write_reply(reply: &Reply, out: &mut Vec<u8>) {
+    match reply {
+        Reply::Ok => out.extend_from_slice(b"+OK\r\n"),
+        Reply::Queued => out.extend_from_slice(b"+QUEUED\r\n"),
+        Reply::Int(n) => out.extend_from_slice(format!(":{}\r\n", n).as_bytes()),
+        Reply::Bulk(None) => out.extend_from_slice(b"$-1\r\n"),
+        Reply::Bulk(Some(bytes)) => {
+            out.extend_from_slice(format!("${}\r\n", bytes.len()).as_bytes());
+            out.extend_from_slice(bytes);
+            out.extend_from_slice(b"\r\n");
+        }
+        Reply::Array(items) => {
+            out.extend_from_slice(format!("*{}\r\n", items.len()).as_bytes());
+            items.iter().for_each(|item| write_reply(item, out));
+        }
+        Reply::Error(msg) => out.extend_from_slice(format!("-{}\r\n", msg).as_bytes()),
+    }
+}
@@ -0,0 +1,174 @@
+mod run_sigi;
+
+use run_sigi::{fresh_dir, json_rows, piping, sigi};
+
+/// Two stacks, and a completed item in the first one's history.
+fn setup(stack: &str, other: &str, args: &[&str]) {
+    for item in ["Buy milk", "Call mom", "buy MILK again"] {
+        sigi(stack, &[args, &["push", item]].concat()).assert_success();
+    }
+    sigi(stack, &[args, &["complete"]].concat()).assert_success();
+    sigi(other, &[args, &["push", "Milk the cows"]].concat()).assert_success();
+}
+
+#[test]
+fn sigi_find_in_stack() {
+    let stack = "_integ::find";
+    let other = "_integ::find_other";
+    let dir = fresh_dir("find");
+    setup(stack, other, &["--data-dir", &dir]);
+
+    let res = sigi(stack, &["--data-dir", &dir, "find", "milk"]);
+    res.assert_success();
+    res.assert_stdout_eq("_integ::find 1: Buy milk\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "find", "-i", "MILK"]);
+    res.assert_stdout_eq("_integ::find 1: Buy milk\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "find", "--regex", "^C.*m$"]);
+    res.assert_stdout_eq("_integ::find Now: Call mom\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "find", "bread"]);
+    res.assert_success();
+    res.assert_stdout_eq("Found: NOTHING\n");
+}
+
+#[test]
+fn sigi_find_everywhere() {
+    let stack = "_integ::find_all";
+    let other = "_integ::find_all_other";
+    let dir = fresh_dir("find_all");
+    setup(stack, other, &["--data-dir", &dir]);
+
+    let res = sigi(stack, &["--data-dir", &dir, "find", "-i", "--all", "milk"]);
+    res.assert_stdout_eq(
+        "_integ::find_all 1: Buy milk\n_integ::find_all_other Now: Milk the cows\n",
+    );
+
+    let res = sigi(
+        stack,
+        &["--data-dir", &dir, "find", "-i", "--history", "milk"],
+    );
+    res.assert_stdout_eq(
+        "_integ::find_all 1: Buy milk\n_integ::find_all_history Now: buy MILK again\n",
+    );
+
+    let res = sigi(
+        stack,
+        &[
+            "--data-dir",
+            &dir,
+            "-f",
+            "json",
+            "find",
+            "-i",
+            "--all",
+            "--history",
+            "milk",
+        ],
+    );
+    let rows = json_rows(res.stdout());
+    let found: Vec<(&str, &str, &str)> = rows
+        .iter()
+        .map(|row| {
+            (
+                row["stack"].as_str().unwrap(),
+                row["position"].as_str().unwrap(),
+                row["item"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("_integ::find_all", "1", "Buy milk"),
+            ("_integ::find_all_history", "0", "buy MILK again"),
+            ("_integ::find_all_other", "0", "Milk the cows"),
+        ]
+    );
+    assert_ne!(rows[0]["id"], "");
+}
+
+#[test]
+fn sigi_find_formats() {
+    let stack = "_integ::find_formats";
+    let other = "_integ::find_formats_other";
+    let dir = fresh_dir("find_formats");
+    setup(stack, other, &["--data-dir", &dir]);
+
+    let res = sigi(stack, &["--data-dir", &dir, "-q", "find", "-i", "milk"]);
+    res.assert_stdout_eq("Buy milk\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "-f", "tsv", "find", "mom"]);
+    let lines: Vec<&str> = res.stdout().lines().collect();
+    assert_eq!(lines[0], "stack\titem\tposition\tid");
+    assert!(lines[1].starts_with("_integ::find_formats\tCall mom\t0\t"));
+
+    let res = sigi(stack, &["--data-dir", &dir, "-f", "csv", "find", "bread"]);
+    res.assert_stdout_eq("");
+}
+
+#[test]
+fn sigi_find_invalid() {
+    let stack = "_integ::find_invalid";
+    let dir = fresh_dir("find_invalid");
+
+    let res = sigi(stack, &["--data-dir", &dir, "find", "--regex", "(milk"]);
+    res.assert_exit_code(2);
+
+    // Without --regex, it's just text.
+    let res = sigi(stack, &["--data-dir", &dir, "find", "(milk"]);
+    res.assert_success();
+
+    let res = sigi(stack, &["--data-dir", &dir, "find"]);
+    res.assert_exit_code(2);
+}
+
+#[test]
+fn sigi_find_interactive() {
+    let stack = "_integ::find_interactive";
+    let dir = fresh_dir("find_interactive");
+
+    let res = piping(&[
+        "push Buy milk",
+        "push Call mom",
+        "find MILK",
+        "find --regex ^call",
+        "find --regex (",
+    ])
+    .into_sigi(stack, &["--data-dir", &dir, "-"]);
+    res.assert_stderr_empty();
+    res.assert_stdout_eq("Buy milk\nCall mom\n");
+}
+
+#[test]
+fn sigi_find_interactive_invalid() {
+    let stack = "_integ::find_interactive_invalid";
+    let dir = fresh_dir("find_interactive_invalid");
+
+    let res =
+        piping(&["find --regex (milk"]).into_sigi(stack, &["--data-dir", &dir, "interactive"]);
+    res.assert_stderr_empty();
+    res.assert_stdout_line_starts_with("parse error: regex parse error:");
+    res.assert_stdout_line_eq("error: unclosed group");
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sigi_find_on_sqlite() {
+    let stack = "_integ::find_sqlite";
+    let other = "_integ::find_sqlite_other";
+    let dir = fresh_dir("find_sqlite");
+    let args = run_sigi::backend_args(&dir, "sqlite");
+    setup(stack, other, &args);
+
+    let res = sigi(
+        stack,
+        &[
+            &args[..],
+            &["-q", "find", "-i", "--all", "--history", "milk"],
+        ]
+        .concat(),
+    );
+    res.assert_stdout_eq("Buy milk\nbuy MILK again\nMilk the cows\n");
+}
@@ -0,0 +1,61 @@
+mod run_sigi;
+
+use run_sigi::sigi;
+
+#[test]
+fn sigi_completions_every_shell() {
+    for shell in ["bash", "zsh", "fish", "elvish", "powershell"] {
+        let res = sigi("_integ::completions", &["completions", shell]);
+        res.assert_success();
+        res.assert_stderr_empty();
+        // Every shell asks sigi for stack names.
+        let hook = "sigi --quiet list-stacks 2>";
+        assert!(res.stdout().contains(hook), "{}", shell);
+    }
+}
+
+#[test]
+fn sigi_completions_know_stack_names() {
+    let res = sigi("_integ::completions", &["completions", "bash"]);
+    assert!(res.stdout().contains("complete -F _sigi_with_stacks"));
+
+    let res = sigi("_integ::completions", &["completions", "fish"]);
+    assert!(res.stdout().contains("sigi --quiet list-stacks"));
+
+    let res = sigi("_integ::completions", &["completions", "zsh"]);
+    res.assert_stdout_line_starts_with("#compdef sigi");
+    assert!(res.stdout().contains(":STACK:_sigi_stacks"));
+
+    let res = sigi("_integ::completions", &["completions", "elvish"]);
+    assert!(res.stdout().contains("$sigi-generated-completer $@words"));
+
+    // The hook runs before the generated completions.
+    let res = sigi("_integ::completions", &["completions", "powershell"]);
+    let script = res.stdout();
+    let hook = script
+        .find("[CompletionResultType]::ParameterValue")
+        .unwrap();
+    let generated = script.find("$completions = @(switch ($command)").unwrap();
+    assert!(hook < generated);
+}
+
+#[test]
+fn sigi_completions_unknown_shell() {
+    let res = sigi("_integ::completions", &["completions", "tcsh"]);
+    res.assert_exit_code(2);
+}
+
+#[test]
+fn sigi_manpage() {
+    let res = sigi("_integ::manpage", &["manpage"]);
+    res.assert_success();
+    let page = res.stdout();
+    assert!(page.contains(".TH sigi 1"));
+    for section in [".SH NAME", ".SH SYNOPSIS", ".SH OPTIONS", ".SH SUBCOMMANDS"] {
+        assert!(page.contains(section), "{}", section);
+    }
+    assert!(page.contains(".SH \"INTERACTIVE MODE\""));
+    // Commands come from the same definitions as --help.
+    assert!(page.contains("list\\-stacks"));
+    assert!(page.contains("completions"));
+}
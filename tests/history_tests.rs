@@ -0,0 +1,105 @@
+mod run_sigi;
+
+use run_sigi::{fresh_dir, piping, sigi};
+
+fn setup(stack: &str, dir: &str) {
+    let res = piping(&[
+        "push milk",
+        "push eggs",
+        "push bread",
+        "complete",
+        "delete",
+        "restore 0",
+    ])
+    .into_sigi(stack, &["--data-dir", dir, "-"]);
+    res.assert_stderr_empty();
+}
+
+#[test]
+fn sigi_history_all_events() {
+    let stack = "_integ::history";
+    let dir = fresh_dir("history");
+    setup(stack, &dir);
+
+    let res = sigi(stack, &["--data-dir", &dir, "history"]);
+    res.assert_success();
+    res.assert_stdout_line_starts_with("restored: eggs (");
+    res.assert_stdout_line_starts_with("deleted: eggs (");
+    res.assert_stdout_line_starts_with("completed: bread (");
+    res.assert_stdout_line_starts_with("created: milk (");
+    res.assert_stderr_empty();
+}
+
+#[test]
+fn sigi_history_by_event() {
+    let stack = "_integ::history_event";
+    let dir = fresh_dir("history_event");
+    setup(stack, &dir);
+
+    let res = sigi(
+        stack,
+        &[
+            "--data-dir",
+            &dir,
+            "-q",
+            "history",
+            "--event",
+            "completed,deleted",
+        ],
+    );
+    res.assert_success();
+    res.assert_stdout_lines_eq(&["eggs", "bread"]);
+
+    let res = sigi(
+        stack,
+        &[
+            "--data-dir",
+            &dir,
+            "-f",
+            "tsv",
+            "history",
+            "--event",
+            "restored",
+        ],
+    );
+    res.assert_success();
+    res.assert_stdout_lines_eq(&["event\titem\tat\tid\tseries\tdetail"]);
+    res.assert_stdout_line_starts_with("restored\teggs\t");
+}
+
+#[test]
+fn sigi_history_by_date_and_text() {
+    let stack = "_integ::history_date";
+    let dir = fresh_dir("history_date");
+    setup(stack, &dir);
+
+    let res = sigi(
+        stack,
+        &["--data-dir", &dir, "history", "--until", "2001-01-01"],
+    );
+    res.assert_success();
+    res.assert_stdout_eq("History: NOTHING\n");
+
+    let res = sigi(
+        stack,
+        &[
+            "--data-dir",
+            &dir,
+            "-f",
+            "json-compact",
+            "history",
+            "--since",
+            "2001-01-01",
+            "--search",
+            "MILK",
+        ],
+    );
+    res.assert_success();
+    res.assert_stdout_line_starts_with("[{\"event\":\"created\",\"item\":\"milk\",\"at\":");
+
+    let res = sigi(
+        stack,
+        &["--data-dir", &dir, "history", "--since", "someday"],
+    );
+    res.assert_exit_code(2);
+}
@@ -0,0 +1,156 @@
+mod run_sigi;
+
+use run_sigi::{fresh_dir, sigi};
+use std::fs;
+
+/// The (item, id) pairs from `list -f json`, most recent first.
+fn list_ids(stack: &str, args: &[&str]) -> Vec<(String, String)> {
+    let args = [args, &["-f", "json", "list"]].concat();
+    let res = sigi(stack, &args);
+    res.assert_success();
+    let rows: Vec<serde_json::Value> = serde_json::from_str(res.stdout()).unwrap();
+    rows.iter()
+        .map(|row| {
+            let field = |key: &str| row[key].as_str().unwrap().to_string();
+            (field("item"), field("id"))
+        })
+        .collect()
+}
+
+fn assert_id_shaped(id: &str) {
+    assert_eq!(id.len(), 6, "Unexpected ID: {:?}", id);
+    assert!(
+        id.chars().all(|c| "bcdfghjkmnpqrstvwxz".contains(c)),
+        "Unexpected ID: {:?}",
+        id
+    );
+}
+
+#[test]
+fn sigi_ids_are_stable() {
+    let stack = "_integ::ids";
+    let dir = fresh_dir("ids");
+
+    for item in ["milk", "eggs", "bread"] {
+        sigi(stack, &["--data-dir", &dir, "push", item]).assert_success();
+    }
+
+    let ids = list_ids(stack, &["--data-dir", &dir]);
+    assert_eq!(ids.len(), 3);
+    ids.iter().for_each(|(_, id)| assert_id_shaped(id));
+    assert_ne!(ids[0].1, ids[1].1);
+    assert_ne!(ids[1].1, ids[2].1);
+
+    // Reordering doesn't change an item's ID.
+    sigi(stack, &["--data-dir", &dir, "swap"]).assert_success();
+    let swapped = list_ids(stack, &["--data-dir", &dir]);
+    assert_eq!(swapped[0], ids[1]);
+    assert_eq!(swapped[1], ids[0]);
+
+    // IDs are only shown in verbose and programmatic output.
+    let res = sigi(stack, &["--data-dir", &dir, "peek"]);
+    res.assert_stdout_eq("Now: eggs\n");
+    let res = sigi(stack, &["--data-dir", &dir, "-v", "peek"]);
+    res.assert_stdout_eq(&format!("Now: eggs ({})\n", ids[1].1));
+    let res = sigi(stack, &["--data-dir", &dir, "-f", "json-compact", "peek"]);
+    res.assert_stdout_line_starts_with(&format!(
+        "[{{\"position\":\"Now\",\"item\":\"eggs\",\"id\":\"{}\",",
+        ids[1].1
+    ));
+}
+
+#[test]
+fn sigi_pick_by_id() {
+    let stack = "_integ::ids_pick";
+    let dir = fresh_dir("ids_pick");
+
+    for item in ["milk", "eggs", "bread"] {
+        sigi(stack, &["--data-dir", &dir, "push", item]).assert_success();
+    }
+    let ids = list_ids(stack, &["--data-dir", &dir]);
+    let milk = &ids[2].1;
+
+    let res = sigi(stack, &["--data-dir", &dir, "pick", milk]);
+    res.assert_success();
+    res.assert_stdout_line_eq("Now: milk");
+
+    // Positions and IDs can be mixed, and unknown IDs are ignored.
+    let res = sigi(stack, &["--data-dir", &dir, "pick", "bcdfgh", "2"]);
+    res.assert_success();
+    let res = sigi(stack, &["--data-dir", &dir, "list"]);
+    res.assert_stdout_eq("Now: eggs\n  1: milk\n  2: bread\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "pick", "milk"]);
+    res.assert_exit_code(2);
+}
+
+#[test]
+fn sigi_restore_by_id() {
+    let stack = "_integ::ids_restore";
+    let dir = fresh_dir("ids_restore");
+
+    for item in ["milk", "eggs", "bread"] {
+        sigi(stack, &["--data-dir", &dir, "push", item]).assert_success();
+    }
+    let ids = list_ids(stack, &["--data-dir", &dir]);
+    sigi(stack, &["--data-dir", &dir, "delete-all"]).assert_success();
+
+    let res = sigi(
+        stack,
+        &["--data-dir", &dir, "-f", "csv", "restore", &ids[1].1],
+    );
+    res.assert_success();
+    res.assert_stdout_eq(&format!("action,item,id\nRestored,eggs,{}\n", ids[1].1));
+
+    // Restoring keeps the ID.
+    assert_eq!(list_ids(stack, &["--data-dir", &dir]), vec![ids[1].clone()]);
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sigi_ids_on_sqlite() {
+    let stack = "_integ::ids_sqlite";
+    let dir = fresh_dir("ids_sqlite");
+    let args = run_sigi::backend_args(&dir, "sqlite");
+
+    for item in ["milk", "eggs"] {
+        sigi(stack, &[&args[..], &["push", item]].concat()).assert_success();
+    }
+    let ids = list_ids(stack, &args);
+    ids.iter().for_each(|(_, id)| assert_id_shaped(id));
+
+    let res = sigi(stack, &[&args[..], &["pick", &ids[1].1]].concat());
+    res.assert_success();
+    let picked = list_ids(stack, &args);
+    assert_eq!(picked, vec![ids[1].clone(), ids[0].clone()]);
+}
+
+#[test]
+fn sigi_ids_backfilled() {
+    let stack = "_integ::ids_backfill";
+    let dir = fresh_dir("ids_backfill");
+    fs::create_dir_all(&dir).unwrap();
+
+    // Saved before items had IDs.
+    let old = r#"[{"contents":"milk","history":[["created","2021-01-01T00:00:00+00:00"]]},{"contents":"eggs","history":[["created","2021-01-02T00:00:00+00:00"]]}]"#;
+    fs::write(format!("{}/{}.json", dir, stack), old).unwrap();
+
+    let ids = list_ids(stack, &["--data-dir", &dir]);
+    assert_eq!(ids.len(), 2);
+    ids.iter().for_each(|(_, id)| assert_id_shaped(id));
+    assert_ne!(ids[0].1, ids[1].1);
+
+    // The same IDs are given every time, and kept once saved.
+    assert_eq!(list_ids(stack, &["--data-dir", &dir]), ids);
+    sigi(stack, &["--data-dir", &dir, "swap"]).assert_success();
+    let saved = fs::read_to_string(format!("{}/{}.json", dir, stack)).unwrap();
+    assert!(saved.contains(&format!("\"id\":\"{}\"", ids[0].1)));
+
+    // Sigi v1 files get IDs too.
+    let v1_stack = "_integ::ids_backfill_v1";
+    let v1 =
+        r#"[{"name":"milk","created":"2021-01-01T00:00:00+00:00","succeeded":null,"failed":null}]"#;
+    fs::write(format!("{}/{}.json", dir, v1_stack), v1).unwrap();
+    let v1_ids = list_ids(v1_stack, &["--data-dir", &dir]);
+    assert_eq!(v1_ids, vec![ids[1].clone()]);
+}
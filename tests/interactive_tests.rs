@@ -1,6 +1,7 @@
 mod run_sigi;
 
-use run_sigi::{piping, sigi};
+use run_sigi::{fresh_dir, piping, sigi};
+use std::fs;
 
 #[test]
 fn sigi_interactive_preamble() {
@@ -47,3 +48,31 @@ fn sigi_interactive_basic_semicolons() {
         "Ctrl+d: Buen biåhe!",
     ]);
 }
+
+#[test]
+fn sigi_interactive_keeps_history() {
+    let stack = "_integ::interactive_history";
+    let dir = fresh_dir("interactive_history");
+    let history = format!("{}/interactive_history.txt", dir);
+
+    piping(&["push hello", "peek"])
+        .into_sigi(stack, &["--data-dir", &dir, "interactive"])
+        .assert_stderr_empty();
+    piping(&["list"])
+        .into_sigi(stack, &["--data-dir", &dir, "interactive"])
+        .assert_stderr_empty();
+
+    let lines = fs::read_to_string(&history).unwrap();
+    let lines: Vec<&str> = lines
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect();
+    assert_eq!(lines, ["push hello", "peek", "list"]);
+
+    // Piped commands aren't typed in, so they aren't kept.
+    piping(&["push goodbye"])
+        .into_sigi(stack, &["--data-dir", &dir, "-"])
+        .assert_stderr_empty();
+    let after = fs::read_to_string(&history).unwrap();
+    assert!(!after.contains("goodbye"));
+}
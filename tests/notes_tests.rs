@@ -0,0 +1,148 @@
+mod run_sigi;
+
+use run_sigi::{fresh_dir, piping, sigi, sigi_with_env};
+use std::fs;
+
+/// An "editor" that replaces the file with this text.
+#[cfg(unix)]
+fn fake_editor(dir: &str, text: &str) -> String {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = format!("{}/editor.sh", dir);
+    let script = format!("#!/bin/sh\nprintf '{}' > \"$1\"\n", text);
+    fs::write(&path, script).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[test]
+#[cfg(unix)]
+fn sigi_edit_in_editor() {
+    let stack = "_integ::notes_edit";
+    let dir = fresh_dir("notes_edit");
+
+    for item in ["Buy milk", "Call mom"] {
+        sigi(stack, &["--data-dir", &dir, "push", item]).assert_success();
+    }
+
+    let editor = fake_editor(
+        &dir,
+        "Buy oat milk +shop\\n\\nFrom the corner shop.\\nThe green carton.\\n",
+    );
+    let res = sigi_with_env(
+        stack,
+        &[("VISUAL", &editor)],
+        &["--data-dir", &dir, "edit", "1"],
+    );
+    res.assert_success();
+    res.assert_stdout_eq("Edited: Buy oat milk +shop\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "show", "1"]);
+    res.assert_stdout_eq("1: Buy oat milk +shop\n  From the corner shop.\n  The green carton.\n");
+
+    // Notes stay out of the way elsewhere.
+    let res = sigi(stack, &["--data-dir", &dir, "list"]);
+    res.assert_stdout_eq("Now: Call mom\n  1: Buy oat milk +shop\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "-f", "json", "show", "1"]);
+    let rows: Vec<serde_json::Value> = serde_json::from_str(res.stdout()).unwrap();
+    assert_eq!(rows[0]["position"], "1");
+    assert_eq!(rows[0]["notes"], "From the corner shop.\nThe green carton.");
+    assert_eq!(rows[0]["tags"], "shop");
+
+    let res = sigi(
+        stack,
+        &["--data-dir", &dir, "-q", "history", "--event", "edited"],
+    );
+    res.assert_stdout_eq("Buy oat milk +shop\n");
+}
+
+#[test]
+#[cfg(unix)]
+fn sigi_edit_unchanged_or_empty() {
+    let stack = "_integ::notes_unchanged";
+    let dir = fresh_dir("notes_unchanged");
+
+    sigi(stack, &["--data-dir", &dir, "push", "Buy milk"]).assert_success();
+
+    for text in ["Buy milk\\n", ""] {
+        let editor = fake_editor(&dir, text);
+        let res = sigi_with_env(stack, &[("VISUAL", &editor)], &["--data-dir", &dir, "edit"]);
+        res.assert_success();
+        res.assert_stdout_eq("Edited: NOTHING\n");
+    }
+
+    let res = sigi_with_env(stack, &[("VISUAL", "false")], &["--data-dir", &dir, "edit"]);
+    res.assert_exit_code(3);
+
+    let res = sigi(
+        stack,
+        &["--data-dir", &dir, "-q", "history", "--event", "edited"],
+    );
+    res.assert_stdout_eq("");
+}
+
+#[test]
+#[cfg(unix)]
+fn sigi_recurring_item_keeps_notes() {
+    let stack = "_integ::notes_recurring";
+    let dir = fresh_dir("notes_recurring");
+
+    let args = [
+        "--data-dir",
+        &dir,
+        "push",
+        "--repeat",
+        "weekly",
+        "Water plants",
+    ];
+    sigi(stack, &args).assert_success();
+    let editor = fake_editor(&dir, "Water plants\\n\\nNot the cactus.\\n");
+    sigi_with_env(stack, &[("VISUAL", &editor)], &["--data-dir", &dir, "edit"]).assert_success();
+
+    sigi(stack, &["--data-dir", &dir, "complete"]).assert_success();
+
+    let res = sigi(stack, &["--data-dir", &dir, "show"]);
+    res.assert_stdout_eq("Now: Water plants\n  Not the cactus.\n");
+}
+
+#[test]
+fn sigi_show_nothing() {
+    let stack = "_integ::notes_nothing";
+    let dir = fresh_dir("notes_nothing");
+
+    let res = sigi(stack, &["--data-dir", &dir, "show"]);
+    res.assert_success();
+    res.assert_stdout_eq("Now: NOTHING\n");
+}
+
+#[test]
+fn sigi_interactive_push_keeps_whitespace() {
+    let stack = "_integ::notes_whitespace";
+    let dir = fresh_dir("notes_whitespace");
+
+    let res =
+        piping(&["push  Tabs\tand  spaces  ", "show"]).into_sigi(stack, &["--data-dir", &dir, "-"]);
+    res.assert_stderr_empty();
+    res.assert_stdout_eq("Tabs\tand  spaces\n");
+}
+
+#[test]
+#[cfg(all(unix, feature = "sqlite"))]
+fn sigi_notes_on_sqlite() {
+    let stack = "_integ::notes_sqlite";
+    let dir = fresh_dir("notes_sqlite");
+    let args = run_sigi::backend_args(&dir, "sqlite");
+
+    sigi(stack, &[&args[..], &["push", "Buy milk"]].concat()).assert_success();
+    let editor = fake_editor(&dir, "Buy milk\\n\\nSemi-skimmed\\n");
+    let res = sigi_with_env(
+        stack,
+        &[("VISUAL", &editor)],
+        &[&args[..], &["edit"]].concat(),
+    );
+    res.assert_success();
+
+    let res = sigi(stack, &[&args[..], &["show"]].concat());
+    res.assert_stdout_eq("Now: Buy milk\n  Semi-skimmed\n");
+}
@@ -0,0 +1,64 @@
+mod run_sigi;
+
+use run_sigi::{sigi, sigi_in, SIGI_DATA_DIR};
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn sigi_project_dir() {
+    let stack = "_integ::project";
+    let project = PathBuf::from(SIGI_DATA_DIR).join("project_dir");
+    let _ = fs::remove_dir_all(&project);
+    let subdir = project.join("some").join("subdir");
+    fs::create_dir_all(&subdir).unwrap();
+    let project = project.canonicalize().unwrap();
+    let sigi_dir = project.join(".sigi");
+
+    let res = sigi_in(&project, stack, &["init"]);
+    res.assert_success();
+    res.assert_stdout_eq(&format!("Initialized: {}\n", sigi_dir.display()));
+    res.assert_stderr_empty();
+    assert!(sigi_dir.is_dir());
+
+    let res = sigi_in(&project, stack, &["init"]);
+    res.assert_success();
+    res.assert_stdout_eq(&format!("Already initialized: {}\n", sigi_dir.display()));
+    res.assert_stderr_empty();
+
+    // Commands in a subdirectory find the project's stacks.
+    let res = sigi_in(&subdir, stack, &["push", "project", "work"]);
+    res.assert_success();
+    res.assert_stdout_eq("Created: project work\n");
+    res.assert_stderr_empty();
+    assert!(sigi_dir.join(format!("{}.json", stack)).is_file());
+
+    let res = sigi_in(&project, stack, &[]);
+    res.assert_success();
+    res.assert_stdout_eq("Now: project work\n");
+    res.assert_stderr_empty();
+
+    // Outside of the project, the stack isn't there.
+    let res = sigi(stack, &["peek"]);
+    res.assert_success();
+    res.assert_stdout_eq("Now: NOTHING\n");
+    res.assert_stderr_empty();
+
+    let res = sigi_in(&subdir, stack, &["store", "--format", "csv"]);
+    res.assert_success();
+    res.assert_stdout_eq(&format!(
+        "backend,location,source\njson,{},project\n",
+        sigi_dir.display()
+    ));
+    res.assert_stderr_empty();
+
+    // An explicit data directory still wins.
+    let elsewhere = project.join("elsewhere");
+    let res = sigi_in(
+        &subdir,
+        stack,
+        &["--data-dir", &elsewhere.to_string_lossy(), "store", "-q"],
+    );
+    res.assert_success();
+    res.assert_stdout_eq(&format!("{}\n", elsewhere.display()));
+    res.assert_stderr_empty();
+}
@@ -0,0 +1,194 @@
+mod run_sigi;
+
+use chrono::{DateTime, Datelike, Days, Local, Weekday};
+use run_sigi::{fresh_dir, json_rows, sigi};
+
+fn due_of(row: &serde_json::Value) -> DateTime<Local> {
+    let due = row["due"].as_str().unwrap();
+    DateTime::parse_from_rfc2822(due)
+        .unwrap()
+        .with_timezone(&Local)
+}
+
+#[test]
+fn sigi_complete_recurring_item() {
+    let stack = "_integ::recurrence";
+    let dir = fresh_dir("recurrence");
+
+    sigi(stack, &["--data-dir", &dir, "push", "Buy milk"]).assert_success();
+    sigi(
+        stack,
+        &[
+            "--data-dir",
+            &dir,
+            "push",
+            "--repeat",
+            "every 3 days",
+            "--due",
+            "2099-01-01",
+            "Rotate on-call notes",
+        ],
+    )
+    .assert_success();
+
+    let res = sigi(stack, &["--data-dir", &dir, "complete"]);
+    res.assert_success();
+    res.assert_stdout_eq(
+        "Completed: Rotate on-call notes\nRepeats: Rotate on-call notes\nNow: Rotate on-call notes\n",
+    );
+
+    let res = sigi(stack, &["--data-dir", &dir, "-f", "json", "list"]);
+    let rows = json_rows(res.stdout());
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0]["item"], "Rotate on-call notes");
+    assert_eq!(rows[0]["recurrence"], "every 3 days");
+    let due = due_of(&rows[0]);
+    assert_eq!((due.year(), due.month(), due.day()), (2099, 1, 4));
+    assert_eq!(rows[1]["item"], "Buy milk");
+    assert_eq!(rows[1]["recurrence"], "");
+}
+
+#[test]
+fn sigi_recurring_item_overdue() {
+    let stack = "_integ::recurrence_overdue";
+    let dir = fresh_dir("recurrence_overdue");
+
+    for (repeat, item) in [("weekly", "Water plants"), ("mon,thu", "Take out trash")] {
+        sigi(
+            stack,
+            &[
+                "--data-dir",
+                &dir,
+                "push",
+                "--repeat",
+                repeat,
+                "--due",
+                "2020-01-01",
+                item,
+            ],
+        )
+        .assert_success();
+    }
+    sigi(stack, &["--data-dir", &dir, "complete"]).assert_success();
+    sigi(stack, &["--data-dir", &dir, "pick", "1"]).assert_success();
+    sigi(stack, &["--data-dir", &dir, "complete"]).assert_success();
+
+    // Late items come back due next time, not due in the past.
+    let now = Local::now();
+    let res = sigi(stack, &["--data-dir", &dir, "-f", "json", "list"]);
+    let rows = json_rows(res.stdout());
+    assert_eq!(rows[0]["item"], "Water plants");
+    let due = due_of(&rows[0]);
+    assert!(due > now && due <= now + Days::new(7));
+    assert_eq!(due.weekday(), Weekday::Wed);
+
+    assert_eq!(rows[1]["item"], "Take out trash");
+    let due = due_of(&rows[1]);
+    assert!(due > now && due <= now + Days::new(7));
+    assert!(matches!(due.weekday(), Weekday::Mon | Weekday::Thu));
+}
+
+#[test]
+fn sigi_recurring_series_history() {
+    let stack = "_integ::recurrence_history";
+    let dir = fresh_dir("recurrence_history");
+
+    sigi(
+        stack,
+        &["--data-dir", &dir, "push", "--repeat", "daily", "Stretch"],
+    )
+    .assert_success();
+    sigi(stack, &["--data-dir", &dir, "push", "Buy milk"]).assert_success();
+    sigi(stack, &["--data-dir", &dir, "complete"]).assert_success();
+    sigi(stack, &["--data-dir", &dir, "complete"]).assert_success();
+    sigi(stack, &["--data-dir", &dir, "complete"]).assert_success();
+
+    let res = sigi(stack, &["--data-dir", &dir, "-f", "json", "peek"]);
+    let id = json_rows(res.stdout())[0]["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    // Every occurrence, from any one of them.
+    let res = sigi(
+        stack,
+        &["--data-dir", &dir, "-q", "history", "--series", &id],
+    );
+    res.assert_success();
+    res.assert_stdout_eq("Stretch\nStretch\nStretch\nStretch\nStretch\n");
+
+    let res = sigi(
+        stack,
+        &[
+            "--data-dir",
+            &dir,
+            "-f",
+            "json",
+            "history",
+            "--series",
+            &id,
+            "--event",
+            "completed",
+        ],
+    );
+    let rows = json_rows(res.stdout());
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0]["series"], rows[1]["series"]);
+    assert_ne!(rows[0]["id"], rows[1]["id"]);
+}
+
+#[test]
+fn sigi_delete_recurring_item() {
+    let stack = "_integ::recurrence_delete";
+    let dir = fresh_dir("recurrence_delete");
+
+    sigi(
+        stack,
+        &["--data-dir", &dir, "push", "--repeat", "daily", "Stretch"],
+    )
+    .assert_success();
+    let res = sigi(stack, &["--data-dir", &dir, "delete"]);
+    res.assert_stdout_eq("Deleted: Stretch\nNow: NOTHING\n");
+}
+
+#[test]
+fn sigi_recurrence_invalid() {
+    let stack = "_integ::recurrence_invalid";
+    let dir = fresh_dir("recurrence_invalid");
+
+    for repeat in ["sometimes", "every 0 days", "mon,someday"] {
+        let res = sigi(
+            stack,
+            &["--data-dir", &dir, "push", "--repeat", repeat, "x"],
+        );
+        res.assert_exit_code(2);
+    }
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sigi_recurrence_on_sqlite() {
+    let stack = "_integ::recurrence_sqlite";
+    let dir = fresh_dir("recurrence_sqlite");
+    let args = run_sigi::backend_args(&dir, "sqlite");
+
+    let push = [&args[..], &["push", "--repeat", "weekly", "Water plants"]].concat();
+    sigi(stack, &push).assert_success();
+    sigi(stack, &[&args[..], &["complete"]].concat()).assert_success();
+
+    let res = sigi(stack, &[&args[..], &["-f", "json", "list"]].concat());
+    let rows = json_rows(res.stdout());
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["recurrence"], "weekly");
+
+    let res = sigi(
+        stack,
+        &[
+            &args[..],
+            &["-f", "json", "history", "--event", "completed"],
+        ]
+        .concat(),
+    );
+    let rows = json_rows(res.stdout());
+    assert_ne!(rows[0]["series"], "");
+}
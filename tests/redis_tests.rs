@@ -0,0 +1,169 @@
+#![cfg(feature = "redis")]
+
+mod fake_redis;
+mod run_sigi;
+
+use run_sigi::sigi;
+use sigi::data::{Backend, Item, Redis};
+use sigi::effects::StackEffect;
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn contents(backend: &dyn Backend, stack: &str) -> Vec<String> {
+    backend
+        .load(stack)
+        .unwrap()
+        .into_iter()
+        .map(|item| item.contents)
+        .collect()
+}
+
+#[test]
+fn redis_stack_ops() {
+    let backend = Redis::open(&fake_redis::start()).unwrap();
+    let stack = || "redis".to_string();
+
+    for content in ["a", "b", "c"] {
+        let push = StackEffect::Push {
+            stack: stack(),
+            item: Item::new(content),
+        };
+        push.run(&backend).unwrap();
+    }
+    assert_eq!(contents(&backend, "redis"), ["a", "b", "c"]);
+
+    StackEffect::Rot { stack: stack() }.run(&backend).unwrap();
+    assert_eq!(contents(&backend, "redis"), ["c", "a", "b"]);
+
+    StackEffect::Complete { stack: stack() }
+        .run(&backend)
+        .unwrap();
+    assert_eq!(contents(&backend, "redis"), ["c", "a"]);
+    assert_eq!(contents(&backend, "redis_history"), ["b"]);
+
+    let move_all = StackEffect::MoveAll {
+        stack: stack(),
+        dest: "elsewhere".to_string(),
+    };
+    move_all.run(&backend).unwrap();
+    assert!(contents(&backend, "redis").is_empty());
+    assert_eq!(contents(&backend, "elsewhere"), ["c", "a"]);
+
+    let mut stacks = backend.list_stacks().unwrap();
+    stacks.sort();
+    assert_eq!(stacks, ["elsewhere", "redis", "redis_history"]);
+}
+
+#[test]
+fn redis_shared_between_clients() {
+    let url = fake_redis::start();
+    let first = Redis::open(&url).unwrap();
+    let second = Redis::open(&url).unwrap();
+
+    let push = StackEffect::Push {
+        stack: "shared".to_string(),
+        item: Item::new("from first"),
+    };
+    push.run(&first).unwrap();
+
+    assert_eq!(contents(&second, "shared"), ["from first"]);
+}
+
+#[test]
+fn sigi_redis_backend() {
+    let url = fake_redis::start();
+    let stack = "_integ::redis";
+    let redis = |args: &[&str]| {
+        let mut all_args = vec!["--backend", "redis", "--redis-url", &url];
+        all_args.extend(args);
+        sigi(stack, &all_args)
+    };
+
+    let res = redis(&["push", "hello"]);
+    res.assert_success();
+    res.assert_stdout_eq("Created: hello\n");
+    res.assert_stderr_empty();
+
+    let res = redis(&["push", "world"]);
+    res.assert_success();
+
+    let res = redis(&["list"]);
+    res.assert_success();
+    res.assert_stdout_lines_eq(&["Now: world", "  1: hello"]);
+    res.assert_stderr_empty();
+
+    let res = redis(&["delete"]);
+    res.assert_success();
+    res.assert_stdout_lines_eq(&["Deleted: world", "Now: hello"]);
+    res.assert_stderr_empty();
+}
+
+#[test]
+fn redis_undo() {
+    let backend = Redis::open(&fake_redis::start()).unwrap();
+    let stack = || "redis_undo".to_string();
+
+    let push = StackEffect::Push {
+        stack: stack(),
+        item: Item::new("a"),
+    };
+    push.run(&backend).unwrap();
+    StackEffect::Complete { stack: stack() }
+        .run(&backend)
+        .unwrap();
+    assert!(contents(&backend, "redis_undo").is_empty());
+
+    StackEffect::Undo { n: 1 }.run(&backend).unwrap();
+    assert_eq!(contents(&backend, "redis_undo"), ["a"]);
+    assert!(contents(&backend, "redis_undo_history").is_empty());
+
+    StackEffect::Redo { n: 1 }.run(&backend).unwrap();
+    assert!(contents(&backend, "redis_undo").is_empty());
+    assert_eq!(contents(&backend, "redis_undo_history"), ["a"]);
+}
+
+#[test]
+fn redis_lock_excludes_other_clients() {
+    let url = fake_redis::start();
+    let first = Redis::open(&url).unwrap();
+
+    let lock = first.lock().unwrap();
+    let waiting = thread::spawn(move || {
+        let second = Redis::open(&url).unwrap();
+        let start = Instant::now();
+        let _lock = second.lock().unwrap();
+        start.elapsed()
+    });
+    thread::sleep(Duration::from_millis(200));
+    drop(lock);
+
+    let waited = waiting.join().unwrap();
+    assert!(waited >= Duration::from_millis(150), "waited {:?}", waited);
+    assert!(first.lock().is_ok());
+}
+
+#[test]
+fn sigi_redis_concurrent_pushes() {
+    let url = fake_redis::start();
+    let stack = "_integ::redis_concurrent";
+    let pushers: Vec<_> = (0..8)
+        .map(|n| {
+            let url = url.clone();
+            thread::spawn(move || {
+                let item = n.to_string();
+                sigi(
+                    stack,
+                    &["--backend", "redis", "--redis-url", &url, "push", &item],
+                )
+                .assert_success();
+            })
+        })
+        .collect();
+    pushers
+        .into_iter()
+        .for_each(|pusher| pusher.join().unwrap());
+
+    let res = sigi(stack, &["--backend", "redis", "--redis-url", &url, "count"]);
+    res.assert_success();
+    res.assert_stdout_eq("8\n");
+}
@@ -0,0 +1,91 @@
+mod run_sigi;
+
+use run_sigi::{fresh_dir, piping, sigi};
+use std::fs;
+
+#[test]
+fn sigi_restore_by_position() {
+    let stack = "_integ::restore";
+    let dir = fresh_dir("restore");
+
+    for item in ["milk", "eggs", "bread"] {
+        sigi(stack, &["--data-dir", &dir, "push", item]).assert_success();
+    }
+    sigi(stack, &["--data-dir", &dir, "complete"]).assert_success();
+    sigi(stack, &["--data-dir", &dir, "delete"]).assert_success();
+
+    // Without positions, the history is shown.
+    let res = sigi(stack, &["--data-dir", &dir, "restore"]);
+    res.assert_success();
+    res.assert_stdout_eq("Now: eggs\n  1: bread\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "restore", "1"]);
+    res.assert_success();
+    res.assert_stdout_eq("Restored: bread\n");
+    res.assert_stderr_empty();
+
+    let res = sigi(stack, &["--data-dir", &dir, "list"]);
+    res.assert_stdout_eq("Now: bread\n  1: milk\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "-f", "csv", "restore", "7"]);
+    res.assert_success();
+    res.assert_stdout_eq("");
+}
+
+#[test]
+fn sigi_restore_by_search() {
+    let stack = "_integ::restore_search";
+    let dir = fresh_dir("restore_search");
+
+    for item in ["Buy milk", "Call mom", "Buy eggs"] {
+        sigi(stack, &["--data-dir", &dir, "push", item]).assert_success();
+    }
+    sigi(stack, &["--data-dir", &dir, "delete-all"]).assert_success();
+
+    let res = sigi(stack, &["--data-dir", &dir, "restore", "--search", "buy"]);
+    res.assert_success();
+    res.assert_stdout_eq("Restored: Buy eggs\nRestored: Buy milk\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "list"]);
+    res.assert_stdout_eq("Now: Buy eggs\n  1: Buy milk\n");
+
+    let res = sigi(&format!("{}_history", stack), &["--data-dir", &dir, "list"]);
+    res.assert_stdout_eq("Now: Call mom\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "restore", "--search", "nope"]);
+    res.assert_stdout_eq("Restored: NOTHING\n");
+}
+
+#[test]
+fn sigi_restore_marks_history() {
+    let stack = "_integ::restore_history";
+    let dir = fresh_dir("restore_history");
+
+    let res =
+        piping(&["push a", "complete", "restore 0"]).into_sigi(stack, &["--data-dir", &dir, "-"]);
+    res.assert_stderr_empty();
+
+    let json = fs::read_to_string(format!("{}/{}.json", dir, stack)).unwrap();
+    let events: Vec<&str> = ["\"created\"", "\"completed\"", "\"restored\""]
+        .into_iter()
+        .filter(|event| json.contains(event))
+        .collect();
+    assert_eq!(events.len(), 3, "Unexpected history: {}", json);
+}
+
+#[test]
+fn sigi_restore_interactive_search() {
+    let stack = "_integ::restore_interactive";
+    let dir = fresh_dir("restore_interactive");
+
+    let res = piping(&[
+        "push a thing",
+        "push other",
+        "delete-all",
+        "restore a thing",
+        "list",
+    ])
+    .into_sigi(stack, &["--data-dir", &dir, "-"]);
+    res.assert_stdout_eq("a thing\n");
+    res.assert_stderr_empty();
+}
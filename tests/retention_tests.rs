@@ -0,0 +1,99 @@
+mod run_sigi;
+
+use run_sigi::{fresh_dir, sigi};
+use std::fs;
+
+fn complete_items(stack: &str, dir: &str, items: &[&str]) {
+    for item in items {
+        sigi(stack, &["--data-dir", dir, "push", item]).assert_success();
+        sigi(stack, &["--data-dir", dir, "complete"]).assert_success();
+    }
+}
+
+#[test]
+fn sigi_prune_max_count() {
+    let stack = "_integ::prune_count";
+    let history = format!("{}_history", stack);
+    let dir = fresh_dir("prune_count");
+    complete_items(stack, &dir, &["a", "b", "c", "d"]);
+
+    // Without a policy, nothing is pruned.
+    let res = sigi(stack, &["--data-dir", &dir, "prune"]);
+    res.assert_success();
+    res.assert_stdout_eq("Pruned: NOTHING\n");
+
+    let res = sigi(
+        stack,
+        &["--data-dir", &dir, "retention", "--max-count", "2"],
+    );
+    res.assert_success();
+    res.assert_stdout_eq("max-age-days: none\nmax-count: 2\nauto-prune: false\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "prune", "--dry-run"]);
+    res.assert_success();
+    res.assert_stdout_eq("Would prune: b\nWould prune: a\n");
+    let res = sigi(&history, &["--data-dir", &dir, "count"]);
+    res.assert_stdout_eq("4\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "-f", "csv", "prune"]);
+    res.assert_success();
+    res.assert_stdout_lines_eq(&["action,item,id"]);
+    res.assert_stdout_line_starts_with("Pruned,b,");
+    res.assert_stdout_line_starts_with("Pruned,a,");
+    let res = sigi(&history, &["--data-dir", &dir, "list"]);
+    res.assert_stdout_eq("Now: d\n  1: c\n");
+}
+
+#[test]
+fn sigi_prune_max_age() {
+    let stack = "_integ::prune_age";
+    let dir = fresh_dir("prune_age");
+    complete_items(stack, &dir, &["recent"]);
+
+    // Add an item that was completed long ago.
+    let history_file = format!("{}/{}_history.json", dir, stack);
+    let recent = fs::read_to_string(&history_file).unwrap();
+    let old = r#"{"contents":"ancient","history":[["created","2001-01-01T00:00:00+00:00"],["completed","2001-01-02T00:00:00+00:00"]]}"#;
+    let with_old = recent.replacen('[', &format!("[{},", old), 1);
+    fs::write(&history_file, with_old).unwrap();
+
+    sigi(stack, &["--data-dir", &dir, "retention", "--max-age", "30"]).assert_success();
+
+    let res = sigi(stack, &["--data-dir", &dir, "prune"]);
+    res.assert_success();
+    res.assert_stdout_eq("Pruned: ancient\n");
+}
+
+#[test]
+fn sigi_auto_prune() {
+    let stack = "_integ::auto_prune";
+    let history = format!("{}_history", stack);
+    let dir = fresh_dir("auto_prune");
+
+    let res = sigi(
+        stack,
+        &[
+            "--data-dir",
+            &dir,
+            "retention",
+            "--max-count",
+            "1",
+            "--auto-prune",
+        ],
+    );
+    res.assert_success();
+
+    complete_items(stack, &dir, &["a", "b", "c"]);
+    let res = sigi(&history, &["--data-dir", &dir, "list"]);
+    res.assert_stdout_eq("Now: c\n");
+
+    // Pruning is part of the completion, so undoing it brings both back.
+    sigi(stack, &["--data-dir", &dir, "undo"]).assert_success();
+    let res = sigi(&history, &["--data-dir", &dir, "list"]);
+    res.assert_stdout_eq("Now: b\n");
+    let res = sigi(stack, &["--data-dir", &dir, "list"]);
+    res.assert_stdout_eq("Now: c\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "retention", "--clear"]);
+    res.assert_stdout_eq("max-age-days: none\nmax-count: none\nauto-prune: false\n");
+}
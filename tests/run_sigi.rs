@@ -1,16 +1,68 @@
-use std::io::{Read, Write};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
 use std::process::{Command, Output, Stdio};
 
 pub const SIGI_PATH: &str = std::env!("CARGO_BIN_EXE_sigi");
 
+/// Where integration tests keep their stacks, so they never touch the real
+/// user data directory.
+pub const SIGI_DATA_DIR: &str = concat!(std::env!("CARGO_TARGET_TMPDIR"), "/sigi-data");
+
+/// An empty data directory of its own under [`SIGI_DATA_DIR`].
+pub fn fresh_dir(name: &str) -> String {
+    let dir = format!("{}/{}", SIGI_DATA_DIR, name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// The arguments that point sigi at `dir` with the given backend.
+pub fn backend_args<'a>(dir: &'a str, backend: &'a str) -> [&'a str; 4] {
+    ["--data-dir", dir, "--backend", backend]
+}
+
+/// Rows of sigi's `-f json` output.
+pub fn json_rows(stdout: &str) -> Vec<serde_json::Value> {
+    serde_json::from_str(stdout).unwrap()
+}
+
 pub fn sigi(stack: &str, args: &[&str]) -> SigiOutput {
-    return Command::new(SIGI_PATH)
+    Command::new(SIGI_PATH)
+        .env("SIGI_DATA_DIR", SIGI_DATA_DIR)
+        .arg("--stack")
+        .arg(stack)
+        .args(args)
+        .output()
+        .expect("Error running process")
+        .into()
+}
+
+/// Run sigi with some extra environment variables, like `EDITOR`.
+pub fn sigi_with_env(stack: &str, env: &[(&str, &str)], args: &[&str]) -> SigiOutput {
+    Command::new(SIGI_PATH)
+        .env("SIGI_DATA_DIR", SIGI_DATA_DIR)
+        .envs(env.iter().copied())
+        .arg("--stack")
+        .arg(stack)
+        .args(args)
+        .output()
+        .expect("Error running process")
+        .into()
+}
+
+/// Run sigi from a specific working directory, without the test data
+/// directory override, so it can discover a project-local ".sigi" directory.
+pub fn sigi_in(dir: &Path, stack: &str, args: &[&str]) -> SigiOutput {
+    Command::new(SIGI_PATH)
+        .current_dir(dir)
+        .env_remove("SIGI_DATA_DIR")
         .arg("--stack")
         .arg(stack)
         .args(args)
         .output()
         .expect("Error running process")
-        .into();
+        .into()
 }
 
 pub fn piping(lines: &[&str]) -> SigiInput {
@@ -27,7 +79,8 @@ impl SigiInput {
     pub fn into_sigi(self, stack: &str, args: &[&str]) -> SigiOutput {
         let stdin = self.stdin.join("\n");
 
-        let process = Command::new(SIGI_PATH)
+        let mut process = Command::new(SIGI_PATH)
+            .env("SIGI_DATA_DIR", SIGI_DATA_DIR)
             .arg("--stack")
             .arg(stack)
             .args(args)
@@ -39,18 +92,18 @@ impl SigiInput {
 
         process
             .stdin
+            .take()
             .expect("Error sending stdin to sigi")
             .write_all(stdin.as_bytes())
             .unwrap();
 
-        let mut stdout = String::new();
-        process.stdout.unwrap().read_to_string(&mut stdout).unwrap();
-
-        let mut stderr = String::new();
-        process.stderr.unwrap().read_to_string(&mut stderr).unwrap();
+        let output = process.wait_with_output().expect("Error running process");
+        let stdout = String::from_utf8(output.stdout).expect("Couldn't read stdout");
+        let stderr = String::from_utf8(output.stderr).expect("Couldn't read stderr");
 
         SigiOutput {
             status: SigiStatus::Unknown,
+            exit_code: output.status.code(),
             stdout,
             stderr,
         }
@@ -59,6 +112,7 @@ impl SigiInput {
 
 pub struct SigiOutput {
     status: SigiStatus,
+    exit_code: Option<i32>,
     stdout: String,
     stderr: String,
 }
@@ -80,6 +134,11 @@ impl From<bool> for SigiStatus {
 }
 
 impl SigiOutput {
+    /// Everything sigi printed to standard output.
+    pub fn stdout(&self) -> &str {
+        &self.stdout
+    }
+
     pub fn assert_success(&self) {
         assert_eq!(self.status, SigiStatus::Success);
     }
@@ -88,6 +147,15 @@ impl SigiOutput {
         assert_eq!(self.status, SigiStatus::Failure);
     }
 
+    pub fn assert_exit_code(&self, expected_code: i32) {
+        assert_eq!(
+            self.exit_code,
+            Some(expected_code),
+            "sigi exited with an unexpected code.\n{}",
+            self.stderr_for_errors()
+        );
+    }
+
     pub fn assert_stdout_eq(&self, expected_stdout: &str) {
         assert_eq!(
             &self.stdout,
@@ -140,6 +208,15 @@ impl SigiOutput {
         );
     }
 
+    pub fn assert_stderr_eq(&self, expected_stderr: &str) {
+        assert_eq!(
+            &self.stderr,
+            expected_stderr,
+            "sigi stderr did not exactly match expectation.\n{}",
+            self.stderr_for_errors()
+        );
+    }
+
     pub fn assert_stderr_empty(&self) {
         assert_eq!(
             &self.stderr,
@@ -172,6 +249,7 @@ impl From<Output> for SigiOutput {
             } else {
                 SigiStatus::Failure
             },
+            exit_code: output.status.code(),
             stdout: String::from_utf8(output.stdout).expect("Couldn't read stdout"),
             stderr: String::from_utf8(output.stderr).expect("Couldn't read stderr"),
         }
@@ -182,6 +260,7 @@ impl From<Output> for SigiOutput {
 fn assert_success() {
     let output = SigiOutput {
         status: true.into(),
+        exit_code: Some(0),
         stdout: String::new(),
         stderr: String::new(),
     };
@@ -193,6 +272,7 @@ fn assert_success() {
 fn assert_failure() {
     let output = SigiOutput {
         status: false.into(),
+        exit_code: Some(1),
         stdout: String::new(),
         stderr: String::new(),
     };
@@ -200,10 +280,35 @@ fn assert_failure() {
     output.assert_failure();
 }
 
+#[test]
+fn assert_exit_code() {
+    let output = SigiOutput {
+        status: false.into(),
+        exit_code: Some(4),
+        stdout: String::new(),
+        stderr: String::new(),
+    };
+
+    output.assert_exit_code(4);
+}
+
+#[test]
+fn stdout() {
+    let output = SigiOutput {
+        status: true.into(),
+        exit_code: Some(0),
+        stdout: "hello".to_string(),
+        stderr: String::new(),
+    };
+
+    assert_eq!(output.stdout(), "hello");
+}
+
 #[test]
 fn assert_stdout_eq() {
     let output = SigiOutput {
         status: true.into(),
+        exit_code: Some(0),
         stdout: "hello".to_string(),
         stderr: String::new(),
     };
@@ -215,6 +320,7 @@ fn assert_stdout_eq() {
 fn assert_stdout_line_eq() {
     let output = SigiOutput {
         status: true.into(),
+        exit_code: Some(0),
         stdout: "hey\nhello".to_string(),
         stderr: String::new(),
     };
@@ -226,6 +332,7 @@ fn assert_stdout_line_eq() {
 fn assert_stdout_lines_eq() {
     let output = SigiOutput {
         status: true.into(),
+        exit_code: Some(0),
         stdout: "hey\nhello there".to_string(),
         stderr: String::new(),
     };
@@ -237,6 +344,7 @@ fn assert_stdout_lines_eq() {
 fn assert_stdout_line_starts_with() {
     let output = SigiOutput {
         status: true.into(),
+        exit_code: Some(0),
         stdout: "hey\nhello there".to_string(),
         stderr: String::new(),
     };
@@ -244,10 +352,23 @@ fn assert_stdout_line_starts_with() {
     output.assert_stdout_line_starts_with("hello");
 }
 
+#[test]
+fn assert_stderr_eq() {
+    let output = SigiOutput {
+        status: false.into(),
+        exit_code: Some(1),
+        stdout: String::new(),
+        stderr: "oops".to_string(),
+    };
+
+    output.assert_stderr_eq("oops");
+}
+
 #[test]
 fn assert_stderr_empty() {
     let output = SigiOutput {
         status: true.into(),
+        exit_code: Some(0),
         stdout: "hey\nhello there".to_string(),
         stderr: String::new(),
     };
@@ -262,3 +383,45 @@ fn sigi_piping_basic() {
     res.assert_stdout_line_starts_with("sigi 3.6");
     res.assert_stderr_empty();
 }
+
+#[test]
+fn sigi_in_basic() {
+    let res = sigi_in(
+        Path::new(std::env!("CARGO_TARGET_TMPDIR")),
+        "_integ::basic",
+        &["--version"],
+    );
+    res.assert_success();
+    res.assert_stdout_line_starts_with("sigi 3.6");
+    res.assert_stderr_empty();
+}
+
+#[test]
+fn sigi_with_env_basic() {
+    let res = sigi_with_env("_integ::basic", &[("SIGI_BACKEND", "nope")], &["peek"]);
+    res.assert_exit_code(2);
+}
+
+#[test]
+fn fresh_dir_basic() {
+    let dir = fresh_dir("fresh_dir");
+    fs::write(format!("{}/leftover.json", dir), "[]").unwrap();
+
+    let dir = fresh_dir("fresh_dir");
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+}
+
+#[test]
+fn backend_args_basic() {
+    let dir = fresh_dir("backend_args");
+    let args = backend_args(&dir, "json");
+    let res = sigi("_integ::basic", &[&args[..], &["-q", "count"]].concat());
+    res.assert_stdout_eq("0\n");
+}
+
+#[test]
+fn json_rows_basic() {
+    let rows = json_rows(r#"[{"position": "Now", "item": "a"}]"#);
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["item"], "a");
+}
@@ -0,0 +1,169 @@
+mod run_sigi;
+
+use run_sigi::{fresh_dir, piping, sigi};
+
+// Stack effects in comments are written like Factor's, with the top of the
+// stack on the right: https://docs.factorcode.org/content/article-shuffle-words.html
+
+/// A fresh stack holding these items, the last one on top.
+fn stack_of(name: &str, items: &[&str]) -> String {
+    let dir = fresh_dir(&format!("shuffle-{}", name));
+    for item in items {
+        sigi("_integ::shuffle", &["--data-dir", &dir, "push", item]).assert_success();
+    }
+    dir
+}
+
+/// The items, top first.
+fn items(dir: &str) -> Vec<String> {
+    let res = sigi("_integ::shuffle", &["--data-dir", dir, "--quiet", "list"]);
+    res.stdout().lines().map(|s| s.to_string()).collect()
+}
+
+fn shuffle(dir: &str, args: &[&str]) {
+    let args = [&["--data-dir", dir], args].concat();
+    let res = sigi("_integ::shuffle", &args);
+    res.assert_success();
+    res.assert_stderr_empty();
+}
+
+#[test]
+fn sigi_dup() {
+    // ( x -- x x )
+    let dir = stack_of("dup", &["a", "b"]);
+    shuffle(&dir, &["dup"]);
+    assert_eq!(items(&dir), ["b", "b", "a"]);
+
+    // The copy is a new item, with its own ID and history.
+    let res = sigi(
+        "_integ::shuffle",
+        &["--data-dir", &dir, "-f", "csv", "list"],
+    );
+    let ids: Vec<&str> = res
+        .stdout()
+        .lines()
+        .skip(1)
+        .map(|line| line.rsplit(',').nth(5).unwrap())
+        .collect();
+    assert_ne!(ids[0], ids[1]);
+    let res = sigi("_integ::shuffle", &["--data-dir", &dir, "-q", "history"]);
+    assert_eq!(res.stdout().lines().count(), 3);
+}
+
+#[test]
+fn sigi_dup_shows_the_copy() {
+    let dir = stack_of("dup-shows", &["a"]);
+    let res = sigi("_integ::shuffle", &["--data-dir", &dir, "dup"]);
+    res.assert_stdout_eq("Created: a\n");
+}
+
+#[test]
+fn sigi_over() {
+    // ( x y -- x y x )
+    let dir = stack_of("over", &["a", "b", "c"]);
+    shuffle(&dir, &["over"]);
+    assert_eq!(items(&dir), ["b", "c", "b", "a"]);
+}
+
+#[test]
+fn sigi_nip() {
+    // ( x y -- y )
+    let dir = stack_of("nip", &["a", "b", "c"]);
+    let res = sigi("_integ::shuffle", &["--data-dir", &dir, "nip"]);
+    res.assert_stdout_lines_eq(&["Deleted: b", "Now: c"]);
+    assert_eq!(items(&dir), ["c", "a"]);
+
+    // Like delete, the item goes to the history stack.
+    let res = sigi("_integ::shuffle", &["--data-dir", &dir, "-q", "restore"]);
+    res.assert_stdout_eq("b\n");
+}
+
+#[test]
+fn sigi_tuck() {
+    // ( x y -- y x y )
+    let dir = stack_of("tuck", &["a", "b", "c"]);
+    shuffle(&dir, &["tuck"]);
+    assert_eq!(items(&dir), ["c", "b", "c", "a"]);
+}
+
+#[test]
+fn sigi_rot_and_minus_rot() {
+    // -rot is Factor's: ( x y z -- z x y ), sending the top down to third.
+    let dir = stack_of("rot", &["a", "b", "c", "d"]);
+    shuffle(&dir, &["-rot"]);
+    assert_eq!(items(&dir), ["c", "b", "d", "a"]);
+
+    // Sigi's rot has always done the same, which is not Factor's rot.
+    shuffle(&dir, &["rot"]);
+    assert_eq!(items(&dir), ["b", "d", "c", "a"]);
+
+    // Factor's rot, ( x y z -- y z x ), is roll 2.
+    shuffle(&dir, &["roll", "2"]);
+    assert_eq!(items(&dir), ["c", "b", "d", "a"]);
+}
+
+#[test]
+fn sigi_roll_and_unroll() {
+    let dir = stack_of("roll", &["a", "b", "c", "d"]);
+    shuffle(&dir, &["roll", "3"]);
+    assert_eq!(items(&dir), ["a", "d", "c", "b"]);
+    shuffle(&dir, &["unroll", "3"]);
+    assert_eq!(items(&dir), ["d", "c", "b", "a"]);
+
+    // roll 1 is swap, and roll 2 undoes rot.
+    shuffle(&dir, &["roll", "1"]);
+    assert_eq!(items(&dir), ["c", "d", "b", "a"]);
+    shuffle(&dir, &["roll", "2"]);
+    assert_eq!(items(&dir), ["b", "c", "d", "a"]);
+
+    // unroll 2 is rot (and -rot).
+    shuffle(&dir, &["unroll", "2"]);
+    assert_eq!(items(&dir), ["c", "d", "b", "a"]);
+}
+
+#[test]
+fn sigi_roll_past_the_bottom() {
+    let dir = stack_of("roll-past", &["a", "b"]);
+    for args in [["roll", "2"], ["unroll", "5"], ["roll", "0"]] {
+        let res = sigi(
+            "_integ::shuffle",
+            &[&["--data-dir", &dir][..], &args].concat(),
+        );
+        res.assert_success();
+        res.assert_stdout_eq("");
+    }
+    assert_eq!(items(&dir), ["b", "a"]);
+}
+
+#[test]
+fn sigi_shuffle_too_few_items() {
+    let dir = stack_of("too-few", &["a"]);
+    for word in ["over", "nip", "tuck"] {
+        shuffle(&dir, &[word]);
+    }
+    assert_eq!(items(&dir), ["a"]);
+
+    // Like rot, -rot swaps two items.
+    let dir = stack_of("too-few-rot", &["a", "b"]);
+    shuffle(&dir, &["-rot"]);
+    assert_eq!(items(&dir), ["a", "b"]);
+}
+
+#[test]
+fn sigi_shuffle_undo() {
+    let dir = stack_of("undo", &["a", "b", "c"]);
+    shuffle(&dir, &["nip"]);
+    shuffle(&dir, &["dup"]);
+    shuffle(&dir, &["undo", "2"]);
+    assert_eq!(items(&dir), ["c", "b", "a"]);
+}
+
+#[test]
+fn sigi_shuffle_interactive() {
+    let dir = stack_of("interactive", &["a", "b", "c"]);
+    let res = piping(&["-rot", "over", "roll 2", "nip", "roll"])
+        .into_sigi("_integ::shuffle", &["--data-dir", &dir, "-"]);
+    // ( a b c -- c a b ), then ( -- c a b a ), then ( -- c b a a ), then ( -- c b a )
+    assert_eq!(items(&dir), ["a", "b", "c"]);
+    res.assert_stdout_eq("");
+}
@@ -0,0 +1,218 @@
+mod run_sigi;
+
+use run_sigi::{fresh_dir, piping, sigi};
+use std::fs;
+use std::thread;
+use std::time::Duration;
+
+fn setup(stack: &str, args: &[&str]) {
+    for item in ["milk", "eggs", "bread"] {
+        sigi(stack, &[args, &["push", item]].concat()).assert_success();
+    }
+}
+
+#[test]
+fn sigi_snooze_hides_item() {
+    let stack = "_integ::snooze";
+    let dir = fresh_dir("snooze");
+    setup(stack, &["--data-dir", &dir]);
+
+    let res = sigi(stack, &["--data-dir", &dir, "snooze", "2h"]);
+    res.assert_success();
+    res.assert_stdout_eq("Snoozed: bread\nNow: eggs\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "peek"]);
+    res.assert_stdout_eq("Now: eggs\n");
+    let res = sigi(stack, &["--data-dir", &dir, "list"]);
+    res.assert_stdout_eq("Now: eggs\n  1: milk\n");
+    let res = sigi(stack, &["--data-dir", &dir, "head", "1"]);
+    res.assert_stdout_eq("Now: eggs\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "list", "--include-snoozed"]);
+    res.assert_stdout_eq("Now: eggs\n  1: milk\n  2: bread (SNOOZED)\n");
+
+    let res = sigi(
+        stack,
+        &[
+            "--data-dir",
+            &dir,
+            "-f",
+            "json",
+            "tail",
+            "--include-snoozed",
+        ],
+    );
+    let rows: Vec<serde_json::Value> = serde_json::from_str(res.stdout()).unwrap();
+    assert_eq!(rows[2]["item"], "bread");
+    assert_ne!(rows[2]["snoozed"], "");
+    assert_eq!(rows[0]["snoozed"], "");
+}
+
+#[test]
+fn sigi_snoozed_item_wakes_up() {
+    let stack = "_integ::snooze_wake";
+    let dir = fresh_dir("snooze_wake");
+    setup(stack, &["--data-dir", &dir]);
+
+    sigi(stack, &["--data-dir", &dir, "snooze", "1d"]).assert_success();
+    sigi(stack, &["--data-dir", &dir, "snooze", "30m"]).assert_success();
+
+    // As if the time had passed.
+    let path = format!("{}/{}.json", dir, stack);
+    let saved = fs::read_to_string(&path).unwrap();
+    let mut items: Vec<serde_json::Value> = serde_json::from_str(&saved).unwrap();
+    items[0]["snoozed_until"] = "2020-01-02T00:00:00Z".into();
+    items[1]["snoozed_until"] = "2020-01-01T00:00:00Z".into();
+    fs::write(&path, serde_json::to_string(&items).unwrap()).unwrap();
+
+    // The one that woke up first ends up on top.
+    let res = sigi(stack, &["--data-dir", &dir, "list"]);
+    res.assert_stdout_eq("Now: bread\n  1: eggs\n  2: milk\n");
+
+    let saved = fs::read_to_string(&path).unwrap();
+    assert!(!saved.contains("snoozed_until"));
+}
+
+#[test]
+fn sigi_snooze_history() {
+    let stack = "_integ::snooze_history";
+    let dir = fresh_dir("snooze_history");
+    setup(stack, &["--data-dir", &dir]);
+
+    sigi(stack, &["--data-dir", &dir, "snooze", "tomorrow"]).assert_success();
+
+    let res = sigi(
+        stack,
+        &["--data-dir", &dir, "-q", "history", "--event", "snoozed"],
+    );
+    res.assert_success();
+    res.assert_stdout_eq("bread\n");
+}
+
+#[test]
+fn sigi_snooze_invalid() {
+    let stack = "_integ::snooze_invalid";
+    let dir = fresh_dir("snooze_invalid");
+
+    let res = sigi(stack, &["--data-dir", &dir, "snooze", "someday"]);
+    res.assert_exit_code(2);
+
+    let res = piping(&[
+        "push milk",
+        "snooze",
+        "snooze 2h",
+        "peek",
+        "list --include-snoozed",
+    ])
+    .into_sigi(stack, &["--data-dir", &dir, "-"]);
+    res.assert_stderr_empty();
+    res.assert_stdout_eq("milk\n");
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sigi_snooze_on_sqlite() {
+    let stack = "_integ::snooze_sqlite";
+    let dir = fresh_dir("snooze_sqlite");
+    let args = run_sigi::backend_args(&dir, "sqlite");
+    setup(stack, &args);
+
+    sigi(stack, &[&args[..], &["snooze", "mon"]].concat()).assert_success();
+
+    let res = sigi(stack, &[&args[..], &["-q", "list"]].concat());
+    res.assert_stdout_eq("eggs\nmilk\n");
+    let res = sigi(
+        stack,
+        &[&args[..], &["-q", "list", "--include-snoozed"]].concat(),
+    );
+    res.assert_stdout_eq("eggs\nmilk\nbread\n");
+}
+
+#[test]
+fn sigi_snoozed_items_are_not_current() {
+    let stack = "_integ::snooze_current";
+    let dir = fresh_dir("snooze_current");
+    let args = ["--data-dir", &dir];
+    sigi(stack, &[&args[..], &["push", "a"]].concat()).assert_success();
+    sigi(stack, &[&args[..], &["push", "b"]].concat()).assert_success();
+    sigi(stack, &[&args[..], &["snooze", "2h"]].concat()).assert_success();
+
+    // There's only one item to swap, so nothing happens.
+    let res = sigi(stack, &[&args[..], &["swap"]].concat());
+    res.assert_stdout_eq("");
+    let res = sigi(stack, &[&args[..], &["peek"]].concat());
+    res.assert_stdout_eq("Now: a\n");
+
+    // The current item is the one peek shows.
+    let res = sigi(stack, &[&args[..], &["complete"]].concat());
+    res.assert_stdout_lines_eq(&["Completed: a", "Now: NOTHING"]);
+
+    // With only snoozed items, there's nothing to act on.
+    for command in ["delete", "next", "swap", "nip", "dup"] {
+        sigi(stack, &[&args[..], &[command]].concat()).assert_success();
+    }
+    let res = sigi(stack, &[&args[..], &["move", stack]].concat());
+    res.assert_success();
+    let res = sigi(
+        stack,
+        &[&args[..], &["-q", "list", "--include-snoozed"]].concat(),
+    );
+    res.assert_stdout_eq("b\n");
+}
+
+#[test]
+fn sigi_shuffles_skip_snoozed_items() {
+    let stack = "_integ::snooze_shuffle";
+    let dir = fresh_dir("snooze_shuffle");
+    let args = ["--data-dir", &dir];
+    setup(stack, &args);
+    sigi(stack, &[&args[..], &["snooze", "2h"]].concat()).assert_success();
+    // Bring the snoozed item back up between the others.
+    sigi(stack, &[&args[..], &["pick", "1", "2"]].concat()).assert_success();
+    let res = sigi(
+        stack,
+        &[&args[..], &["-q", "list", "--include-snoozed"]].concat(),
+    );
+    res.assert_stdout_eq("milk\nbread\neggs\n");
+
+    sigi(stack, &[&args[..], &["swap"]].concat()).assert_success();
+    let res = sigi(
+        stack,
+        &[&args[..], &["-q", "list", "--include-snoozed"]].concat(),
+    );
+    res.assert_stdout_eq("eggs\nbread\nmilk\n");
+
+    sigi(stack, &[&args[..], &["next"]].concat()).assert_success();
+    let res = sigi(
+        stack,
+        &[&args[..], &["-q", "list", "--include-snoozed"]].concat(),
+    );
+    res.assert_stdout_eq("bread\nmilk\neggs\n");
+
+    let res = sigi(stack, &[&args[..], &["delete"]].concat());
+    res.assert_stdout_lines_eq(&["Deleted: milk", "Now: eggs"]);
+    let res = sigi(stack, &[&args[..], &["-q", "show"]].concat());
+    res.assert_stdout_line_starts_with("eggs");
+}
+
+#[test]
+fn sigi_undo_after_waking() {
+    let stack = "_integ::snooze_wake_undo";
+    let dir = fresh_dir("snooze_wake_undo");
+    let args = ["--data-dir", &dir];
+    let soon = (chrono::Local::now() + chrono::Duration::seconds(1)).to_rfc3339();
+    sigi(stack, &[&args[..], &["push", "X"]].concat()).assert_success();
+    sigi(stack, &[&args[..], &["snooze", &soon]].concat()).assert_success();
+    sigi(stack, &[&args[..], &["push", "Y"]].concat()).assert_success();
+
+    thread::sleep(Duration::from_millis(1200));
+    // Waking X puts it back on top, outside of the journal.
+    let res = sigi(stack, &[&args[..], &["peek"]].concat());
+    res.assert_stdout_eq("Now: X\n");
+
+    // Undoing the push still takes out Y, not X.
+    let res = sigi(stack, &[&args[..], &["undo"]].concat());
+    res.assert_stdout_eq("Undone: push\n");
+    let res = sigi(stack, &[&args[..], &["list"]].concat());
+    res.assert_stdout_eq("Now: X\n");
+}
@@ -0,0 +1,126 @@
+#![cfg(feature = "sqlite")]
+
+mod run_sigi;
+
+use run_sigi::{backend_args, fresh_dir, sigi};
+use sigi::data::{Backend, Item, Sqlite};
+use sigi::effects::StackEffect;
+use std::fs;
+use std::path::PathBuf;
+
+fn fresh_database(name: &str) -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("{}.sqlite", name));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn contents(backend: &dyn Backend, stack: &str) -> Vec<String> {
+    backend
+        .load(stack)
+        .unwrap()
+        .into_iter()
+        .map(|item| item.contents)
+        .collect()
+}
+
+#[test]
+fn sqlite_stack_ops() {
+    let path = fresh_database("sqlite_stack_ops");
+    let backend = Sqlite::open_at(&path).unwrap();
+    let stack = || "sqlite".to_string();
+
+    for content in ["a", "b", "c", "d"] {
+        let push = StackEffect::Push {
+            stack: stack(),
+            item: Item::new(content),
+        };
+        push.run(&backend).unwrap();
+    }
+    assert_eq!(contents(&backend, "sqlite"), ["a", "b", "c", "d"]);
+
+    StackEffect::Swap { stack: stack() }.run(&backend).unwrap();
+    assert_eq!(contents(&backend, "sqlite"), ["a", "b", "d", "c"]);
+
+    StackEffect::Delete { stack: stack() }
+        .run(&backend)
+        .unwrap();
+    StackEffect::Complete { stack: stack() }
+        .run(&backend)
+        .unwrap();
+    assert_eq!(contents(&backend, "sqlite"), ["a", "b"]);
+    assert_eq!(contents(&backend, "sqlite_history"), ["c", "d"]);
+
+    let history = backend.load("sqlite_history").unwrap();
+    let events = |i: usize| {
+        history[i]
+            .history
+            .iter()
+            .map(|event| event.name.as_str())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(events(0), ["created", "deleted"]);
+    assert_eq!(events(1), ["created", "completed"]);
+
+    StackEffect::DeleteAll { stack: stack() }
+        .run(&backend)
+        .unwrap();
+    assert!(contents(&backend, "sqlite").is_empty());
+    assert_eq!(contents(&backend, "sqlite_history"), ["c", "d", "a", "b"]);
+
+    let mut stacks = backend.list_stacks().unwrap();
+    stacks.sort();
+    assert_eq!(stacks, ["sqlite", "sqlite_history"]);
+}
+
+#[test]
+fn sqlite_persists_between_connections() {
+    let path = fresh_database("sqlite_persists");
+
+    let backend = Sqlite::open_at(&path).unwrap();
+    let push = StackEffect::Push {
+        stack: "persisted".to_string(),
+        item: Item::new("still here"),
+    };
+    push.run(&backend).unwrap();
+    drop(backend);
+
+    let backend = Sqlite::open_at(&path).unwrap();
+    assert_eq!(contents(&backend, "persisted"), ["still here"]);
+}
+
+#[test]
+fn sqlite_imports_another_store() {
+    let source = Sqlite::open_at(&fresh_database("sqlite_import_source")).unwrap();
+    for content in ["x", "y"] {
+        let push = StackEffect::Push {
+            stack: "imported".to_string(),
+            item: Item::new(content),
+        };
+        push.run(&source).unwrap();
+    }
+
+    let dest = Sqlite::open_at(&fresh_database("sqlite_import_dest")).unwrap();
+    dest.import(&source).unwrap();
+    assert_eq!(contents(&dest, "imported"), ["x", "y"]);
+}
+
+#[test]
+fn sigi_sqlite_imports_metadata() {
+    let stack = "_integ::sqlite_import_meta";
+    let dir = fresh_dir("sqlite_import_meta");
+    let json = ["--data-dir", &dir];
+    let sqlite = backend_args(&dir, "sqlite");
+
+    sigi(stack, &[&json[..], &["push", "a"]].concat()).assert_success();
+    sigi(stack, &[&json[..], &["push", "b"]].concat()).assert_success();
+    let policy = ["retention", "--max-count", "5", "--auto-prune"];
+    sigi(stack, &[&json[..], &policy].concat()).assert_success();
+
+    // The first use of SQLite brings the policy and the undo journal along.
+    let res = sigi(stack, &[&sqlite[..], &["retention"]].concat());
+    res.assert_stdout_eq("max-age-days: none\nmax-count: 5\nauto-prune: true\n");
+    let res = sigi(stack, &[&sqlite[..], &["undo"]].concat());
+    res.assert_stdout_eq("Undone: push\n");
+    let res = sigi(stack, &[&sqlite[..], &["-q", "list"]].concat());
+    res.assert_stdout_eq("a\n");
+}
@@ -0,0 +1,165 @@
+mod run_sigi;
+
+use run_sigi::{fresh_dir, piping, sigi};
+use std::fs;
+
+fn setup(stack: &str, dir: &str) {
+    for item in [
+        "Write docs +review",
+        "Fix pager #OnCall",
+        "Buy milk",
+        "Check alerts +oncall +review",
+    ] {
+        sigi(stack, &["--data-dir", dir, "push", item]).assert_success();
+    }
+}
+
+#[test]
+fn sigi_tags_from_content() {
+    let stack = "_integ::tags";
+    let dir = fresh_dir("tags");
+    setup(stack, &dir);
+
+    let res = sigi(stack, &["--data-dir", &dir, "-f", "json", "list"]);
+    res.assert_success();
+    let rows: Vec<serde_json::Value> = serde_json::from_str(res.stdout()).unwrap();
+    assert_eq!(rows[0]["item"], "Check alerts +oncall +review");
+    let tags: Vec<&str> = rows
+        .iter()
+        .map(|row| row["tags"].as_str().unwrap())
+        .collect();
+    assert_eq!(tags, vec!["oncall review", "", "oncall", "review"]);
+}
+
+#[test]
+fn sigi_push_with_tag_option() {
+    let stack = "_integ::tags_option";
+    let dir = fresh_dir("tags_option");
+
+    let res = sigi(
+        stack,
+        &[
+            "--data-dir",
+            &dir,
+            "push",
+            "--tag",
+            "review",
+            "--tag",
+            "+docs",
+            "Write",
+            "docs",
+        ],
+    );
+    res.assert_success();
+    res.assert_stdout_eq("Created: Write docs +review +docs\n");
+
+    // Tags already in the content aren't written twice.
+    let res = sigi(
+        stack,
+        &[
+            "--data-dir",
+            &dir,
+            "push",
+            "--tag",
+            "review",
+            "Read +review",
+        ],
+    );
+    res.assert_stdout_eq("Created: Read +review\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "push", "--tag", "42", "nope"]);
+    res.assert_exit_code(2);
+}
+
+#[test]
+fn sigi_filter_by_tag() {
+    let stack = "_integ::tags_filter";
+    let dir = fresh_dir("tags_filter");
+    setup(stack, &dir);
+
+    // Positions are still positions in the whole stack.
+    let res = sigi(stack, &["--data-dir", &dir, "list", "--tag", "oncall"]);
+    res.assert_success();
+    res.assert_stdout_eq("Now: Check alerts +oncall +review\n  2: Fix pager #OnCall\n");
+
+    let res = sigi(
+        stack,
+        &[
+            "--data-dir",
+            &dir,
+            "list",
+            "--tag",
+            "+review",
+            "--tag",
+            "oncall",
+        ],
+    );
+    res.assert_stdout_eq("Now: Check alerts +oncall +review\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "head", "1", "--tag", "review"]);
+    res.assert_stdout_eq("Now: Check alerts +oncall +review\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "tail", "1", "--tag", "review"]);
+    res.assert_stdout_eq("  3: Write docs +review\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "count", "--tag", "review"]);
+    res.assert_stdout_eq("2\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "peek", "--tag", "oncall"]);
+    res.assert_stdout_eq("Now: Check alerts +oncall +review\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "peek", "--tag", "nothing"]);
+    res.assert_stdout_eq("Now: NOTHING\n");
+
+    let res = sigi(
+        stack,
+        &["--data-dir", &dir, "-q", "list", "--tag", "nothing"],
+    );
+    res.assert_stdout_eq("");
+}
+
+#[test]
+fn sigi_filter_by_tag_interactive() {
+    let stack = "_integ::tags_interactive";
+    let dir = fresh_dir("tags_interactive");
+    setup(stack, &dir);
+
+    let res =
+        piping(&["list #oncall", "count +review"]).into_sigi(stack, &["--data-dir", &dir, "-"]);
+    res.assert_stderr_empty();
+    res.assert_stdout_eq("Check alerts +oncall +review\nFix pager #OnCall\n2\n");
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sigi_tags_on_sqlite() {
+    let stack = "_integ::tags_sqlite";
+    let dir = fresh_dir("tags_sqlite");
+    let args = run_sigi::backend_args(&dir, "sqlite");
+
+    for item in ["Buy milk", "Fix pager #oncall"] {
+        sigi(stack, &[&args[..], &["push", item]].concat()).assert_success();
+    }
+
+    let res = sigi(stack, &[&args[..], &["count", "--tag", "oncall"]].concat());
+    res.assert_stdout_eq("1\n");
+    let res = sigi(stack, &[&args[..], &["-f", "json", "peek"]].concat());
+    let rows: Vec<serde_json::Value> = serde_json::from_str(res.stdout()).unwrap();
+    assert_eq!(rows[0]["item"], "Fix pager #oncall");
+    assert_eq!(rows[0]["tags"], "oncall");
+}
+
+#[test]
+fn sigi_tags_backfilled() {
+    let stack = "_integ::tags_backfill";
+    let dir = fresh_dir("tags_backfill");
+    fs::create_dir_all(&dir).unwrap();
+
+    // Saved before items had tags.
+    let old =
+        r#"[{"contents":"Fix pager +oncall","history":[["created","2021-01-01T00:00:00+00:00"]]}]"#;
+    fs::write(format!("{}/{}.json", dir, stack), old).unwrap();
+
+    let res = sigi(stack, &["--data-dir", &dir, "count", "--tag", "oncall"]);
+    res.assert_stdout_eq("1\n");
+}
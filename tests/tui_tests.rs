@@ -0,0 +1,131 @@
+#![cfg(feature = "tui")]
+
+mod run_sigi;
+
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::Terminal;
+use run_sigi::{fresh_dir, sigi};
+use sigi::cli::tui::run_tui_with_keys;
+use sigi::data::HomeDir;
+use std::path::Path;
+
+/// A stack in a fresh data directory, holding these items, the last one on top.
+fn stack_of(name: &str, items: &[&str]) -> String {
+    let dir = fresh_dir(&format!("tui-{}", name));
+    for item in items {
+        sigi("_integ::tui", &["--data-dir", &dir, "push", item]).assert_success();
+    }
+    dir
+}
+
+/// Run the UI on a test terminal with these keys, and return what it showed last.
+fn tui(dir: &str, keys: &[KeyCode]) -> String {
+    let backend = HomeDir::at(Path::new(dir));
+    let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+    let keys = keys.iter().map(|&code| KeyEvent::from(code));
+    run_tui_with_keys("_integ::tui".to_string(), &backend, &mut terminal, keys).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let width = buffer.area.width as usize;
+    buffer
+        .content
+        .chunks(width)
+        .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn contents(dir: &str, stack: &str) -> String {
+    sigi(stack, &["--data-dir", dir, "-q", "list"])
+        .stdout()
+        .to_string()
+}
+
+#[test]
+fn sigi_tui_needs_terminal() {
+    let stack = "_integ::tui";
+    let dir = fresh_dir("tui");
+
+    sigi(stack, &["--data-dir", &dir, "push", "Buy milk"]).assert_success();
+
+    let res = sigi(stack, &["--data-dir", &dir, "tui"]);
+    res.assert_exit_code(3);
+
+    // Nothing changed.
+    let res = sigi(stack, &["--data-dir", &dir, "list"]);
+    res.assert_stdout_eq("Now: Buy milk\n");
+}
+
+#[test]
+fn tui_shows_stack() {
+    let dir = stack_of("shows", &["Buy milk", "Call mom"]);
+
+    let screen = tui(&dir, &[]);
+    assert!(screen.contains("_integ::tui (2)"), "{}", screen);
+    assert!(screen.contains("Now: Call mom"), "{}", screen);
+    assert!(screen.contains("  1: Buy milk"), "{}", screen);
+}
+
+#[test]
+fn tui_move_selected_up() {
+    let dir = stack_of("move_up", &["a", "b", "c"]);
+
+    // The selection follows the item as it moves up.
+    let (down, up) = (KeyCode::Char('j'), KeyCode::Char('K'));
+    tui(&dir, &[down, down, up]);
+    assert_eq!(contents(&dir, "_integ::tui"), "c\na\nb\n");
+    tui(&dir, &[down, down, up, up, up]);
+    assert_eq!(contents(&dir, "_integ::tui"), "b\nc\na\n");
+}
+
+#[test]
+fn tui_move_selected_down() {
+    let dir = stack_of("move_down", &["a", "b", "c"]);
+
+    let down = KeyCode::Char('J');
+    tui(&dir, &[down]);
+    assert_eq!(contents(&dir, "_integ::tui"), "b\nc\na\n");
+    tui(&dir, &[down, down, down]);
+    assert_eq!(contents(&dir, "_integ::tui"), "c\na\nb\n");
+}
+
+#[test]
+fn tui_run_on_selected() {
+    let dir = stack_of("selected", &["a", "b", "c"]);
+
+    let screen = tui(&dir, &[KeyCode::Char('j'), KeyCode::Char('c')]);
+    assert!(screen.contains("Completed: b"), "{}", screen);
+    assert_eq!(contents(&dir, "_integ::tui"), "c\na\n");
+
+    // The top item is picked first too.
+    let screen = tui(&dir, &[KeyCode::Char('d')]);
+    assert!(screen.contains("Deleted: c"), "{}", screen);
+
+    // Picking and completing are undone together.
+    let screen = tui(&dir, &[KeyCode::Char('u'), KeyCode::Char('u')]);
+    assert!(screen.contains("Undone: complete"), "{}", screen);
+    assert_eq!(contents(&dir, "_integ::tui"), "c\nb\na\n");
+}
+
+#[test]
+fn tui_switch_stacks() {
+    let dir = stack_of("switch", &["a"]);
+    let other = "_integ::tui_other";
+    sigi(other, &["--data-dir", &dir, "push", "x"]).assert_success();
+    sigi(other, &["--data-dir", &dir, "push", "y"]).assert_success();
+
+    let keys = [
+        KeyCode::Tab,
+        KeyCode::Char('j'),
+        KeyCode::Enter,
+        KeyCode::Char('j'),
+        KeyCode::Char('c'),
+    ];
+    let screen = tui(&dir, &keys);
+    assert!(screen.contains("_integ::tui_other (1)"), "{}", screen);
+    assert!(screen.contains("Completed: x"), "{}", screen);
+
+    assert_eq!(contents(&dir, other), "y\n");
+    assert_eq!(contents(&dir, "_integ::tui"), "a\n");
+}
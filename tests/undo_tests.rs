@@ -0,0 +1,147 @@
+mod run_sigi;
+
+use run_sigi::{backend_args, fresh_dir, piping, sigi};
+use std::fs;
+
+fn undo_delete_all_and_move(backend: &str) {
+    let stack = "_integ::undo";
+    let other = "_integ::undo_other";
+    let dir = fresh_dir(&format!("undo_{}", backend));
+    let sigi = |stack: &str, args: &[&str]| {
+        let mut all_args = backend_args(&dir, backend).to_vec();
+        all_args.extend(args);
+        sigi(stack, &all_args)
+    };
+
+    sigi(stack, &["push", "a"]).assert_success();
+    sigi(stack, &["push", "b"]).assert_success();
+    sigi(stack, &["move", other]).assert_success();
+    sigi(stack, &["delete-all"]).assert_success();
+
+    let res = sigi(stack, &["undo"]);
+    res.assert_success();
+    res.assert_stdout_eq("Undone: delete-all\n");
+    res.assert_stderr_empty();
+
+    let res = sigi(stack, &["list"]);
+    res.assert_stdout_eq("Now: a\n");
+    let res = sigi(&format!("{}_history", stack), &["count"]);
+    res.assert_stdout_eq("0\n");
+
+    // Undoing a move puts the item back on its original stack.
+    let res = sigi(stack, &["undo"]);
+    res.assert_stdout_eq("Undone: move\n");
+    let res = sigi(stack, &["list"]);
+    res.assert_stdout_eq("Now: b\n  1: a\n");
+    let res = sigi(other, &["count"]);
+    res.assert_stdout_eq("0\n");
+
+    let res = sigi(stack, &["redo", "2"]);
+    res.assert_stdout_eq("Redone: move\nRedone: delete-all\n");
+    let res = sigi(stack, &["count"]);
+    res.assert_stdout_eq("0\n");
+    let res = sigi(other, &["list"]);
+    res.assert_stdout_eq("Now: b\n");
+
+    let res = sigi(stack, &["redo"]);
+    res.assert_stdout_eq("Redone: NOTHING\n");
+}
+
+#[test]
+fn sigi_undo_json() {
+    undo_delete_all_and_move("json");
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sigi_undo_sqlite() {
+    undo_delete_all_and_move("sqlite");
+}
+
+#[test]
+fn sigi_undo_many() {
+    let stack = "_integ::undo_many";
+    let dir = fresh_dir("undo_many");
+
+    for item in ["a", "b", "c"] {
+        sigi(stack, &["--data-dir", &dir, "push", item]).assert_success();
+    }
+    // Reading doesn't count as something to undo.
+    sigi(stack, &["--data-dir", &dir, "list"]).assert_success();
+
+    let res = sigi(stack, &["--data-dir", &dir, "--format=csv", "undo", "5"]);
+    res.assert_success();
+    res.assert_stdout_lines_eq(&[
+        "action,effect,stack",
+        "Undone,push,_integ::undo_many",
+        "Undone,push,_integ::undo_many",
+        "Undone,push,_integ::undo_many",
+    ]);
+
+    // A new change means there's nothing left to redo.
+    sigi(stack, &["--data-dir", &dir, "push", "d"]).assert_success();
+    let res = sigi(stack, &["--data-dir", &dir, "redo"]);
+    res.assert_stdout_eq("Redone: NOTHING\n");
+}
+
+#[test]
+fn sigi_undo_interactive() {
+    let stack = "_integ::undo_interactive";
+    let dir = fresh_dir("undo_interactive");
+
+    let res = piping(&["push a", "push b", "complete", "undo", "list"])
+        .into_sigi(stack, &["--data-dir", &dir, "-"]);
+    res.assert_stdout_eq("b\na\n");
+    res.assert_stderr_empty();
+}
+
+#[test]
+fn sigi_undo_finds_items_by_id() {
+    let stack = "_integ::undo_by_id";
+    let dir = fresh_dir("undo_by_id");
+    sigi(stack, &["--data-dir", &dir, "push", "a"]).assert_success();
+    sigi(stack, &["--data-dir", &dir, "push", "b"]).assert_success();
+
+    // Reorder the stack without sigi knowing.
+    let file = format!("{}/{}.json", dir, stack);
+    let mut items: Vec<serde_json::Value> =
+        serde_json::from_str(&fs::read_to_string(&file).unwrap()).unwrap();
+    items.reverse();
+    fs::write(&file, serde_json::to_string(&items).unwrap()).unwrap();
+
+    // Undoing the push takes out what was pushed, not whatever is on top.
+    let res = sigi(stack, &["--data-dir", &dir, "undo"]);
+    res.assert_stdout_eq("Undone: push\n");
+    let res = sigi(stack, &["--data-dir", &dir, "list"]);
+    res.assert_stdout_eq("Now: a\n");
+
+    let res = sigi(stack, &["--data-dir", &dir, "redo"]);
+    res.assert_stdout_eq("Redone: push\n");
+    let res = sigi(stack, &["--data-dir", &dir, "list"]);
+    res.assert_stdout_eq("Now: b\n  1: a\n");
+}
+
+#[test]
+fn sigi_undo_journals_only_what_changed() {
+    let stack = "_integ::undo_diffs";
+    let dir = fresh_dir("undo_diffs");
+    let pushes: Vec<String> = (0..100).map(|n| format!("push item {}", n)).collect();
+    let pushes: Vec<&str> = pushes.iter().map(String::as_str).collect();
+    piping(&pushes).into_sigi(stack, &["--data-dir", &dir, "-"]);
+
+    let journal = format!("{}/sigi.journal", dir);
+    let stack_size = fs::metadata(format!("{}/{}.json", dir, stack))
+        .unwrap()
+        .len();
+    let journal_size = fs::metadata(&journal).unwrap().len();
+
+    // Cycling moves one item, so that's all there is to remember.
+    sigi(stack, &["--data-dir", &dir, "next"]).assert_success();
+    let growth = fs::metadata(&journal).unwrap().len() - journal_size;
+    assert!(growth < stack_size / 10, "{} of {}", growth, stack_size);
+
+    let res = sigi(stack, &["--data-dir", &dir, "undo"]);
+    res.assert_stdout_eq("Undone: next\n");
+    let res = sigi(stack, &["--data-dir", &dir, "-q", "head", "2"]);
+    res.assert_stdout_eq("item 99\nitem 98\n");
+}